@@ -0,0 +1,98 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use wave_function_collapse::{
+    board::Board,
+    cell::Cell,
+    grid::Direction,
+    puzzles::Sudoku,
+    solver::SolverBuilder,
+    tiles::{expand, Sides, TileRules},
+};
+
+type Classic = Sudoku<3, 3>;
+type SudokuCell = Cell<u16, { Classic::SIDE }>;
+
+/// The puzzle solved by the sudoku example
+const SUDOKU: &str =
+    "6.....5.9.7..4..6.4........51.4...37....63.........9....29.8...........2.9.7.13..";
+
+const PATH_STATES: usize = 12;
+const PATH_ROW_LEN: usize = 32;
+const PATH_COL_LEN: usize = 16;
+const PATH_SIZE: usize = PATH_ROW_LEN * PATH_COL_LEN;
+
+/// Seeds every solve so each run does the same work
+const SEED: u64 = 5;
+
+fn path_neighbors(i: usize) -> Vec<(usize, Direction)> {
+    let mut neighbors = vec![];
+    let (x, y) = (i % PATH_ROW_LEN, i / PATH_ROW_LEN);
+
+    if x > 0 {
+        neighbors.push((i - 1, Direction::Left));
+    }
+
+    if x < PATH_ROW_LEN - 1 {
+        neighbors.push((i + 1, Direction::Right));
+    }
+
+    if y > 0 {
+        neighbors.push((i - PATH_ROW_LEN, Direction::Up));
+    }
+
+    if y < PATH_COL_LEN - 1 {
+        neighbors.push((i + PATH_ROW_LEN, Direction::Down));
+    }
+
+    neighbors
+}
+
+fn path_rules() -> TileRules<u16> {
+    const CORNER: Sides = Sides::LEFT.union(Sides::BOTTOM);
+    const TEE: Sides = Sides::LEFT.union(Sides::RIGHT).union(Sides::TOP);
+    const LINE: Sides = Sides::LEFT.union(Sides::RIGHT);
+
+    TileRules::new(&expand(&[CORNER, TEE, LINE, Sides::ALL, Sides::NONE]))
+}
+
+fn solve_sudoku(c: &mut Criterion) {
+    let state = <[SudokuCell; Classic::CELLS]>::from_chars(SUDOKU, |c| match c {
+        '.' => Some(Cell::default()),
+        '1'..='9' => c.to_digit(10).map(|n| Cell::reduced(n as usize - 1)),
+        _ => None,
+    })
+    .unwrap();
+
+    c.bench_function("solve sudoku", |b| {
+        b.iter(|| {
+            let mut solver = SolverBuilder::new(Classic::neighbors, Classic::reducer)
+                .state(state)
+                .seed(SEED)
+                .build()
+                .unwrap();
+            solver.solve().unwrap();
+            solver
+        })
+    });
+}
+
+fn solve_path(c: &mut Criterion) {
+    let rules = path_rules();
+
+    c.bench_function("solve empty path board", |b| {
+        b.iter(|| {
+            let mut solver = SolverBuilder::<u16, PATH_STATES, PATH_SIZE>::directed(
+                path_neighbors,
+                rules.clone().into_directed_reducer(),
+            )
+            .seed(SEED)
+            .build()
+            .unwrap();
+            solver.solve().unwrap();
+            solver
+        })
+    });
+}
+
+criterion_group!(benches, solve_sudoku, solve_path);
+criterion_main!(benches);
@@ -0,0 +1,148 @@
+use std::fs::File;
+
+use wave_function_collapse::{
+    grid::{grid_neighbors, Direction},
+    render::{render_to_rgba, Color},
+    solver::{SolveStatus, SolverBuilder, SolverState},
+    tiles::{expand, Sides, TileRules},
+};
+
+const STATES: usize = 12;
+const ROW_LEN: usize = 32;
+const COL_LEN: usize = 16;
+const BOARD_SIZE: usize = ROW_LEN * COL_LEN;
+
+/// Each cell is drawn as a 3x3 block of pipe, scaled up by this much
+const SCALE: usize = 4;
+const TILE_PIXELS: usize = 3 * SCALE;
+const WIDTH: usize = ROW_LEN * TILE_PIXELS;
+const HEIGHT: usize = COL_LEN * TILE_PIXELS;
+
+const BACKGROUND: [u8; 4] = [24, 24, 32, u8::MAX];
+
+type CellStorage = u16;
+type BoardState = SolverState<CellStorage, STATES, BOARD_SIZE>;
+
+fn neighbors(i: usize) -> Vec<(usize, Direction)> {
+    grid_neighbors(i, ROW_LEN, COL_LEN)
+}
+
+/// The connected sides of each tile
+fn tiles() -> Vec<Sides> {
+    const CORNER: Sides = Sides::LEFT.union(Sides::BOTTOM);
+    const TEE: Sides = Sides::LEFT.union(Sides::RIGHT).union(Sides::TOP);
+    const LINE: Sides = Sides::LEFT.union(Sides::RIGHT);
+
+    expand(&[CORNER, TEE, LINE, Sides::ALL, Sides::NONE])
+}
+
+/// Pipes are colored by how many sides they connect, so unknown cells fade
+/// from the background towards the pipes they could still become
+fn palette(tiles: &[Sides]) -> Vec<Color> {
+    tiles
+        .iter()
+        .map(|&sides| {
+            [Sides::LEFT, Sides::RIGHT, Sides::TOP, Sides::BOTTOM]
+                .into_iter()
+                .filter(|&side| sides.contains(side))
+                .count()
+        })
+        .map(|connections| match connections {
+            0 => (24, 24, 32),
+            2 => (90, 200, 250),
+            3 => (250, 180, 60),
+            _ => (240, 90, 120),
+        })
+        .collect()
+}
+
+/// Draws each known cell as its pipe and each unknown cell as a solid block
+/// of its blended color
+fn draw(state: &BoardState, tiles: &[Sides], palette: &[Color]) -> Vec<u8> {
+    let colors = render_to_rgba(state, ROW_LEN, palette);
+    let mut pixels = vec![0; WIDTH * HEIGHT * 4];
+
+    for (i, cell) in state.iter().enumerate() {
+        let color = &colors[i * 4..i * 4 + 4];
+
+        for (py, px) in (0..3).flat_map(|py| (0..3).map(move |px| (py, px))) {
+            let lit = match (cell.value(), px, py) {
+                (None, _, _) => true,
+                (Some(n), 1, 1) => tiles[n] != Sides::NONE,
+                (Some(n), 0, 1) => tiles[n].contains(Sides::LEFT),
+                (Some(n), 2, 1) => tiles[n].contains(Sides::RIGHT),
+                (Some(n), 1, 0) => tiles[n].contains(Sides::TOP),
+                (Some(n), 1, 2) => tiles[n].contains(Sides::BOTTOM),
+                _ => false,
+            };
+            let rgba = if lit { color } else { &BACKGROUND };
+
+            let x = i % ROW_LEN * TILE_PIXELS + px * SCALE;
+            let y = i / ROW_LEN * TILE_PIXELS + py * SCALE;
+            for row in y..y + SCALE {
+                let start = (row * WIDTH + x) * 4;
+                for pixel in pixels[start..start + SCALE * 4].chunks_mut(4) {
+                    pixel.copy_from_slice(rgba);
+                }
+            }
+        }
+    }
+
+    pixels
+}
+
+/// Writes an animated GIF of an empty path board being solved.
+///
+/// Usage: `cargo run --example animate -- [fps] [observations per frame] [path]`
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let fps: u16 = args
+        .next()
+        .map(|fps| fps.parse())
+        .transpose()?
+        .unwrap_or(30);
+    let steps: usize = args
+        .next()
+        .map(|steps| steps.parse())
+        .transpose()?
+        .unwrap_or(1);
+    let path = args.next().unwrap_or_else(|| "animate.gif".to_string());
+
+    if fps == 0 || steps == 0 {
+        return Err("The frame rate and observations per frame must be positive".into());
+    }
+
+    let tiles = tiles();
+    let palette = palette(&tiles);
+    let mut solver = SolverBuilder::<CellStorage, STATES, BOARD_SIZE>::directed(
+        neighbors,
+        TileRules::new(&tiles).into_directed_reducer(),
+    )
+    .dimensions(ROW_LEN, COL_LEN)
+    .seed(5)
+    .build()?;
+
+    let mut encoder = gif::Encoder::new(File::create(&path)?, WIDTH as u16, HEIGHT as u16, &[])?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    // GIF delays are in hundredths of a second
+    let delay = (100 / fps).max(1);
+    let mut frames = 0;
+
+    loop {
+        let status = solver.solve_budget(steps);
+        let mut pixels = draw(solver.state(), &tiles, &palette);
+        let mut frame = gif::Frame::from_rgba_speed(WIDTH as u16, HEIGHT as u16, &mut pixels, 10);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+        frames += 1;
+
+        match status {
+            SolveStatus::InProgress => {}
+            SolveStatus::Solved => break,
+            SolveStatus::Failed(e) => return Err(e.into()),
+        }
+    }
+
+    println!("Wrote {} frames to {}", frames, path);
+    Ok(())
+}
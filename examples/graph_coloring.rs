@@ -0,0 +1,69 @@
+use bitvec::{array::BitArray, order::Lsb0};
+use wave_function_collapse::{cell::Cell, graph::neighbors_in, solver::SolverBuilder};
+
+const COLORS: usize = 3;
+const NODES: usize = 10;
+
+/// The Petersen graph, an outer pentagon joined to an inner pentagram, which
+/// needs exactly three colors
+const EDGES: [(usize, usize); 15] = [
+    (0, 1),
+    (1, 2),
+    (2, 3),
+    (3, 4),
+    (4, 0),
+    (0, 5),
+    (1, 6),
+    (2, 7),
+    (3, 8),
+    (4, 9),
+    (5, 7),
+    (7, 9),
+    (9, 6),
+    (6, 8),
+    (8, 5),
+];
+
+const NAMES: [&str; COLORS] = ["red", "green", "blue"];
+
+type CellStorage = u8;
+type CellState = BitArray<CellStorage, Lsb0>;
+type NodeCell = Cell<CellStorage, COLORS>;
+
+fn neighbors(i: usize) -> Vec<usize> {
+    neighbors_in(&EDGES, i)
+}
+
+/// A node cannot share a color with any node it has an edge to
+fn reducer(neighbors: Vec<(usize, &NodeCell)>, _: usize) -> CellState {
+    let mut acc = CellState::ZERO;
+
+    for (_, cell) in neighbors {
+        acc |= cell.state();
+    }
+
+    acc
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver = SolverBuilder::<CellStorage, COLORS, NODES>::new(neighbors, reducer)
+        .seed(0)
+        .build()?;
+    solver.solve()?;
+
+    let colors = solver
+        .state()
+        .iter()
+        .map(|cell| cell.value().ok_or("A node was left uncolored"))
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    for (i, color) in colors.iter().enumerate() {
+        println!("Node {} is {}", i, NAMES[*color]);
+    }
+
+    if let Some((a, b)) = EDGES.iter().find(|&&(a, b)| colors[a] == colors[b]) {
+        return Err(format!("Nodes {} and {} share a color", a, b).into());
+    }
+
+    Ok(())
+}
@@ -1,13 +1,14 @@
-use bitvec::{array::BitArray, order::Lsb0};
 use std::{
-    cmp::Ordering,
-    fmt, thread,
+    thread,
     time::{Duration, SystemTime},
 };
 
 use wave_function_collapse::{
+    board::{Board, ParseError},
     cell::Cell,
-    solver::{Pan, Solver, SolverBuilder},
+    grid::Direction,
+    solver::{InfiniteSolver, Pan, Solver, SolverBuilder},
+    tiles::{expand, Sides, TileSet},
 };
 
 const STATES: usize = 12;
@@ -16,11 +17,10 @@ const COL_LEN: usize = 16;
 const BOARD_SIZE: usize = ROW_LEN * COL_LEN;
 
 type CellStorage = u16;
-type CellState = BitArray<CellStorage, Lsb0>;
 type PathCell = Cell<CellStorage, STATES>;
 type BoardState = [PathCell; BOARD_SIZE];
 
-fn neighbors(i: usize) -> Vec<usize> {
+fn neighbors(i: usize) -> Vec<(usize, Direction)> {
     let mut neighbors = vec![];
     let x = i % ROW_LEN;
     let y = i / ROW_LEN;
@@ -30,129 +30,80 @@ fn neighbors(i: usize) -> Vec<usize> {
     let bottom = y < COL_LEN - 1;
 
     if left {
-        neighbors.push(y * ROW_LEN + x - 1);
+        neighbors.push((y * ROW_LEN + x - 1, Direction::Left));
     }
 
     if right {
-        neighbors.push(y * ROW_LEN + x + 1);
+        neighbors.push((y * ROW_LEN + x + 1, Direction::Right));
     }
 
     if top {
-        neighbors.push((y - 1) * ROW_LEN + x);
+        neighbors.push(((y - 1) * ROW_LEN + x, Direction::Up));
     }
 
     if bottom {
-        neighbors.push((y + 1) * ROW_LEN + x);
+        neighbors.push(((y + 1) * ROW_LEN + x, Direction::Down));
     }
 
     neighbors
 }
 
-fn state_reducer(neighbors: Vec<(usize, &PathCell)>, i: usize) -> CellState {
-    const LEFT_CONNECTED: u16 = 0b0011_0110_1101;
-    const LEFT_DISCONNECTED: u16 = 0b1100_1001_0010;
-    const LEFT_REDUCTIONS: [CellStorage; 12] = [
-        LEFT_CONNECTED,    // ┐
-        LEFT_DISCONNECTED, // └
-        LEFT_DISCONNECTED, // ┴
-        LEFT_DISCONNECTED, // ┬
-        LEFT_DISCONNECTED, // ├
-        LEFT_DISCONNECTED, // ─
-        LEFT_DISCONNECTED, // ┼
-        LEFT_CONNECTED,    // │
-        LEFT_CONNECTED,    // ┤
-        LEFT_CONNECTED,    // ┘
-        LEFT_DISCONNECTED, // ┌
-        LEFT_CONNECTED,    //
-    ];
-    const RIGHT_CONNECTED: u16 = 0b0100_0111_1110;
-    const RIGHT_DISCONNECTED: u16 = 0b1011_1000_0001;
-    const RIGHT_REDUCTIONS: [CellStorage; 12] = [
-        RIGHT_DISCONNECTED, // ┐
-        RIGHT_CONNECTED,    // └
-        RIGHT_DISCONNECTED, // ┴
-        RIGHT_DISCONNECTED, // ┬
-        RIGHT_CONNECTED,    // ├
-        RIGHT_DISCONNECTED, // ─
-        RIGHT_DISCONNECTED, // ┼
-        RIGHT_CONNECTED,    // │
-        RIGHT_DISCONNECTED, // ┤
-        RIGHT_DISCONNECTED, // ┘
-        RIGHT_CONNECTED,    // ┌
-        RIGHT_CONNECTED,    //
-    ];
-    const TOP_CONNECTED: u16 = 0b0011_1101_0110;
-    const TOP_DISCONNECTED: u16 = 0b1100_0010_1001;
-    const TOP_REDUCTIONS: [CellStorage; 12] = [
-        TOP_DISCONNECTED, // ┐
-        TOP_CONNECTED,    // └
-        TOP_CONNECTED,    // ┴
-        TOP_DISCONNECTED, // ┬
-        TOP_DISCONNECTED, // ├
-        TOP_CONNECTED,    // ─
-        TOP_DISCONNECTED, // ┼
-        TOP_DISCONNECTED, // │
-        TOP_DISCONNECTED, // ┤
-        TOP_CONNECTED,    // ┘
-        TOP_DISCONNECTED, // ┌
-        TOP_CONNECTED,    //
-    ];
-    const BOTTOM_CONNECTED: u16 = 0b0101_1101_1001;
-    const BOTTOM_DISCONNECTED: u16 = 0b1010_0010_0110;
-    const BOTTOM_REDUCTIONS: [CellStorage; 12] = [
-        BOTTOM_CONNECTED,    // ┐
-        BOTTOM_DISCONNECTED, // └
-        BOTTOM_DISCONNECTED, // ┴
-        BOTTOM_CONNECTED,    // ┬
-        BOTTOM_DISCONNECTED, // ├
-        BOTTOM_CONNECTED,    // ─
-        BOTTOM_DISCONNECTED, // ┼
-        BOTTOM_DISCONNECTED, // │
-        BOTTOM_DISCONNECTED, // ┤
-        BOTTOM_DISCONNECTED, // ┘
-        BOTTOM_CONNECTED,    // ┌
-        BOTTOM_CONNECTED,    //
-    ];
+/// The connected sides of each tile, in the same order as `GLYPHS`
+fn tiles() -> Vec<Sides> {
+    const CORNER: Sides = Sides::LEFT.union(Sides::BOTTOM);
+    const TEE: Sides = Sides::LEFT.union(Sides::RIGHT).union(Sides::TOP);
+    const LINE: Sides = Sides::LEFT.union(Sides::RIGHT);
 
-    let mut acc = CellState::ZERO;
+    expand(&[CORNER, TEE, LINE, Sides::ALL, Sides::NONE])
+}
 
-    for cell in neighbors {
-        let (j, cell) = cell;
-        let ix = i % ROW_LEN;
-        let iy = i / ROW_LEN;
-        let jx = j % ROW_LEN;
-        let jy = j / ROW_LEN;
-        let result = cell
-            .value()
-            .expect(&format!("Cell {} was uncollapsed: {}", j, cell.state()));
+const GLYPHS: [char; STATES] = ['┐', '┘', '└', '┌', '┴', '├', '┬', '┤', '─', '│', '┼', ' '];
 
-        acc |= match (ix.cmp(&jx), iy.cmp(&jy)) {
-            (Ordering::Greater, Ordering::Equal) => CellState::new(LEFT_REDUCTIONS[result]),
-            (Ordering::Less, Ordering::Equal) => CellState::new(RIGHT_REDUCTIONS[result]),
-            (Ordering::Equal, Ordering::Greater) => CellState::new(TOP_REDUCTIONS[result]),
-            (Ordering::Equal, Ordering::Less) => CellState::new(BOTTOM_REDUCTIONS[result]),
-            (_, _) => unreachable!(),
-        };
+/// Pipes which must connect to their neighbors on every connected side
+struct Pipes;
+
+impl TileSet for Pipes {
+    const N: usize = STATES;
+
+    fn glyph(tile: usize) -> char {
+        GLYPHS[tile]
     }
 
-    acc
+    fn allowed(tile: usize, neighbor: usize, direction: Direction) -> bool {
+        let (side, opposite) = match direction {
+            Direction::Left => (Sides::LEFT, Sides::RIGHT),
+            Direction::Right => (Sides::RIGHT, Sides::LEFT),
+            Direction::Up => (Sides::TOP, Sides::BOTTOM),
+            Direction::Down => (Sides::BOTTOM, Sides::TOP),
+        };
+        let tiles = tiles();
+
+        tiles[tile].contains(side) == tiles[neighbor].contains(opposite)
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let contents = std::fs::read_to_string("examples/wfc.txt")?;
-    let mut solver = SolverBuilder::new(neighbors, state_reducer)
+    let rules = Pipes::rules::<CellStorage>();
+    let builder = SolverBuilder::directed(neighbors, rules.into_directed_reducer())
         .state(parse(&contents)?)
-        .seed(5)
-        .build();
+        .dimensions(ROW_LEN, COL_LEN)
+        .on_collapse(report_progress);
+    // A named seed may be given on the command line to share a map
+    let builder = match std::env::args().nth(1) {
+        Some(seed) => builder.seed_str(&seed),
+        None => builder.seed(5),
+    };
+    let solver = builder.build()?;
+    let mut solver = InfiniteSolver::new(solver, ROW_LEN);
 
-    solver.solve();
-    print_board(&solver);
+    solver.solve()?;
+    print_board(solver.solver());
 
     for _ in 0..8 {
         let start_time = SystemTime::now();
-        solver.pan(Pan::Down(8), ROW_LEN);
-        solver.solve();
-        bottom_rows(8, &solver);
+        solver.advance(Pan::Down(8))?;
+        bottom_rows(8, solver.solver());
         let elapsed = start_time.elapsed()?;
         // println!("took {} ms", elapsed.as_millis());
         thread::sleep(
@@ -165,106 +116,46 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn format_cell(cell: &PathCell) -> String {
-    match cell.value() {
-        Some(0) => format!("{}", '┐'),
-        Some(1) => format!("{}", '└'),
-        Some(2) => format!("{}", '┴'),
-        Some(3) => format!("{}", '┬'),
-        Some(4) => format!("{}", '├'),
-        Some(5) => format!("{}", '─'),
-        Some(6) => format!("{}", '┼'),
-        Some(7) => format!("{}", '│'),
-        Some(8) => format!("{}", '┤'),
-        Some(9) => format!("{}", '┘'),
-        Some(10) => format!("{}", '┌'),
-        Some(11) => format!("{}", ' '),
-        Some(_) | None => format!("({}) ", cell.entropy()),
+/// Reports how much of the board is known as each cell is observed
+fn report_progress(_: usize, _: usize, state: &BoardState) {
+    let known = state.iter().filter(|cell| !cell.is_unknown()).count();
+    eprint!("\r{}/{} cells known", known, BOARD_SIZE);
+
+    if known == BOARD_SIZE {
+        eprintln!();
     }
 }
 
-fn print_board(solver: &Solver<CellStorage, STATES, BOARD_SIZE>) {
-    let state = solver.state();
-    for i in 0..state.len() {
-        let cell = &state[i];
-        print!("{}", format_cell(&cell));
-
-        if (i + 1) % ROW_LEN == 0 {
-            println!();
-        }
+fn format_cell(cell: &PathCell) -> String {
+    match cell.value() {
+        Some(n) => Pipes::glyph(n).to_string(),
+        None => format!("({}) ", cell.entropy()),
     }
 }
 
-fn bottom_rows(rows: usize, solver: &Solver<CellStorage, STATES, BOARD_SIZE>) {
-    let state = solver.state();
-    for i in (ROW_LEN * (COL_LEN - rows))..state.len() {
-        let cell = &state[i];
-        print!("{}", format_cell(&cell));
+fn format_row_cell(i: usize, cell: &PathCell) -> String {
+    let mut text = format_cell(cell);
 
-        if (i + 1) % ROW_LEN == 0 {
-            println!();
-        }
+    if (i + 1).is_multiple_of(ROW_LEN) {
+        text.push('\n');
     }
-}
 
-#[derive(Debug)]
-pub enum ParseError {
-    InvalidSize(usize),
-    InvalidInput(usize, char),
-    InternalError,
+    text
 }
 
-impl std::error::Error for ParseError {}
+fn print_board(solver: &Solver<CellStorage, STATES, BOARD_SIZE>) {
+    print!("{}", solver.state().to_string(format_row_cell));
+}
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        match self {
-            Self::InvalidSize(i) => {
-                write!(f, "A board was provided with an invalid length of {}", i)?
-            }
-            Self::InvalidInput(i, c) => write!(f, "Character {} at position {} is invalid", c, i)?,
-            Self::InternalError => write!(f, "An internal error has occurred")?,
-        }
+fn bottom_rows(rows: usize, solver: &Solver<CellStorage, STATES, BOARD_SIZE>) {
+    let board = solver.state().to_string(format_row_cell);
+    let skip = board.lines().count() - rows;
 
-        Ok(())
+    for line in board.lines().skip(skip) {
+        println!("{}", line);
     }
 }
 
 fn parse(raw: &str) -> Result<BoardState, ParseError> {
-    let raw = raw
-        .chars()
-        .filter(|&c| c != '\r' && c != '\n')
-        .collect::<Vec<char>>();
-    if raw.len() != BOARD_SIZE {
-        return Err(ParseError::InvalidSize(raw.len()));
-    }
-
-    let char_map = |c: (usize, &char)| -> Result<PathCell, ParseError> {
-        match c.1 {
-            '┐' => Ok(Cell::reduced(0)),
-            '└' => Ok(Cell::reduced(1)),
-            '┴' => Ok(Cell::reduced(2)),
-            '┬' => Ok(Cell::reduced(3)),
-            '├' => Ok(Cell::reduced(4)),
-            '─' => Ok(Cell::reduced(5)),
-            '┼' => Ok(Cell::reduced(6)),
-            '│' => Ok(Cell::reduced(7)),
-            '┤' => Ok(Cell::reduced(8)),
-            '┘' => Ok(Cell::reduced(9)),
-            '┌' => Ok(Cell::reduced(10)),
-            ' ' => Ok(Cell::reduced(11)),
-            '.' => Ok(Cell::default()),
-            _ => Err(ParseError::InvalidInput(c.0, *c.1)),
-        }
-    };
-
-    match TryInto::<BoardState>::try_into(
-        raw.iter()
-            .enumerate()
-            .map(char_map)
-            .collect::<Result<Vec<PathCell>, ParseError>>()?,
-    ) {
-        Ok(state) => Ok(state),
-        Err(_) => Err(ParseError::InternalError),
-    }
+    BoardState::from_chars(raw, |c| Pipes::parse_cell(c, '.'))
 }
@@ -0,0 +1,105 @@
+//! A minimal `wasm-bindgen` wrapper around `SolverBuilder`
+//!
+//! Build for the browser without OS entropy using:
+//! ```text
+//! cargo build --example wasm --target wasm32-unknown-unknown --no-default-features
+//! ```
+use bitvec::{array::BitArray, order::Lsb0};
+use wasm_bindgen::prelude::*;
+
+use wave_function_collapse::{
+    cell::Cell,
+    solver::{SolveStatus, Solver, SolverBuilder},
+};
+
+const STATES: usize = 3;
+const ROW_LEN: usize = 8;
+const COL_LEN: usize = 8;
+const BOARD_SIZE: usize = ROW_LEN * COL_LEN;
+
+type CellStorage = u8;
+type CellState = BitArray<CellStorage, Lsb0>;
+type ColorCell = Cell<CellStorage, STATES>;
+
+fn neighbors(i: usize) -> Vec<usize> {
+    let x = i % ROW_LEN;
+    let y = i / ROW_LEN;
+    let mut neighbors = vec![];
+
+    if x > 0 {
+        neighbors.push(i - 1);
+    }
+
+    if x < ROW_LEN - 1 {
+        neighbors.push(i + 1);
+    }
+
+    if y > 0 {
+        neighbors.push(i - ROW_LEN);
+    }
+
+    if y < COL_LEN - 1 {
+        neighbors.push(i + ROW_LEN);
+    }
+
+    neighbors
+}
+
+// Adjacent cells may not share a color
+fn state_reducer(neighbors: Vec<(usize, &ColorCell)>, _: usize) -> CellState {
+    let mut acc = CellState::ZERO;
+
+    for (_, cell) in neighbors {
+        acc |= cell.state();
+    }
+
+    acc
+}
+
+/// A board of three colors where no two adjacent cells match
+#[wasm_bindgen]
+pub struct ColorBoard {
+    solver: Solver<CellStorage, STATES, BOARD_SIZE>,
+}
+
+#[wasm_bindgen]
+impl ColorBoard {
+    /// Creates a new board, the seed is required since browsers have no
+    /// entropy source the solver can draw from
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> Result<ColorBoard, JsError> {
+        let solver = SolverBuilder::new(neighbors, state_reducer)
+            .seed(seed)
+            .build()
+            .map_err(|error| JsError::new(&error.to_string()))?;
+
+        Ok(ColorBoard { solver })
+    }
+
+    pub fn solve(&mut self) -> Result<(), JsError> {
+        // `SolverError` only implements `std::error::Error` with the `std`
+        // feature, so convert through its message instead
+        self.solver
+            .solve()
+            .map_err(|error| JsError::new(&error.to_string()))
+    }
+
+    /// Observes at most `max_steps` cells, so the board can be solved a few
+    /// cells per animation frame. Returns whether every cell is solved
+    pub fn step(&mut self, max_steps: usize) -> Result<bool, JsError> {
+        match self.solver.solve_budget(max_steps) {
+            SolveStatus::InProgress => Ok(false),
+            SolveStatus::Solved => Ok(true),
+            SolveStatus::Failed(error) => Err(JsError::new(&error.to_string())),
+        }
+    }
+
+    /// The color of each cell in row major order, 255 for unsolved cells
+    pub fn colors(&self) -> Vec<u8> {
+        self.solver
+            .state()
+            .iter()
+            .map(|cell| cell.value().map_or(u8::MAX, |n| n as u8))
+            .collect()
+    }
+}
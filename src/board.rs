@@ -0,0 +1,286 @@
+use alloc::{string::String, vec, vec::Vec};
+use bitvec::{array::BitArray, view::BitViewSized};
+use core::fmt::{self, Debug};
+
+use crate::{cell::Cell, solver::SolverState};
+
+/// The version of the format written by `Packed::to_bytes`
+pub const FORMAT_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidSize(usize),
+    InvalidInput(usize, char),
+    InternalError,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::InvalidSize(i) => {
+                write!(f, "A board was provided with an invalid length of {}", i)?
+            }
+            Self::InvalidInput(i, c) => write!(f, "Character {} at position {} is invalid", c, i)?,
+            Self::InternalError => write!(f, "An internal error has occurred")?,
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes were written by an unknown version of the format
+    UnsupportedVersion(u8),
+    /// The number of bytes doesn't match the size of the board
+    InvalidSize(usize),
+    /// The cell at the index has an unknown variant or impossible states
+    InvalidCell(usize),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::UnsupportedVersion(v) => write!(f, "Format version {} is not supported", v)?,
+            Self::InvalidSize(n) => write!(
+                f,
+                "A board was provided with an invalid length of {} bytes",
+                n
+            )?,
+            Self::InvalidCell(i) => write!(f, "Cell {} is invalid", i)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Conversions between a board and a grid of characters
+pub trait Board: Sized {
+    type Cell;
+
+    /// Parses a board with one character per cell, ignoring line breaks.
+    /// `char_map` returns the cell for a character or `None` if it is invalid
+    fn from_chars(
+        raw: &str,
+        char_map: impl Fn(char) -> Option<Self::Cell>,
+    ) -> Result<Self, ParseError>;
+
+    /// Concatenates the text of each cell, `cell_map` receives each cell's
+    /// index so it can insert separators and line breaks
+    fn to_string(&self, cell_map: impl Fn(usize, &Self::Cell) -> String) -> String;
+}
+
+impl<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> Board
+    for SolverState<A, N, S>
+{
+    type Cell = Cell<A, N>;
+
+    fn from_chars(
+        raw: &str,
+        char_map: impl Fn(char) -> Option<Self::Cell>,
+    ) -> Result<Self, ParseError> {
+        let raw = raw
+            .chars()
+            .filter(|&c| c != '\r' && c != '\n')
+            .collect::<Vec<char>>();
+        if raw.len() != S {
+            return Err(ParseError::InvalidSize(raw.len()));
+        }
+
+        match TryInto::<Self>::try_into(
+            raw.into_iter()
+                .enumerate()
+                .map(|(i, c)| char_map(c).ok_or(ParseError::InvalidInput(i, c)))
+                .collect::<Result<Vec<Self::Cell>, ParseError>>()?,
+        ) {
+            Ok(state) => Ok(state),
+            Err(_) => Err(ParseError::InternalError),
+        }
+    }
+
+    fn to_string(&self, cell_map: impl Fn(usize, &Self::Cell) -> String) -> String {
+        self.iter()
+            .enumerate()
+            .map(|(i, cell)| cell_map(i, cell))
+            .collect()
+    }
+}
+
+/// A dense binary encoding of a board, for storing many generated boards.
+/// The bytes start with `FORMAT_VERSION`, followed by each cell as a variant
+/// tag and `ceil(N / 8)` bytes of its states, least significant state first
+pub trait Packed: Sized {
+    fn to_bytes(&self) -> Vec<u8>;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+const UNKNOWN: u8 = 0;
+const REDUCED: u8 = 1;
+const COLLAPSED: u8 = 2;
+
+impl<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> Packed
+    for SolverState<A, N, S>
+{
+    fn to_bytes(&self) -> Vec<u8> {
+        let width = N.div_ceil(8);
+        let mut bytes = vec![0; 1 + S * (1 + width)];
+        bytes[0] = FORMAT_VERSION;
+
+        for (cell, chunk) in self.iter().zip(bytes[1..].chunks_mut(1 + width)) {
+            chunk[0] = match cell {
+                Cell::Unknown(_) => UNKNOWN,
+                Cell::Reduced(_, _) => REDUCED,
+                Cell::Collapsed(_, _) => COLLAPSED,
+            };
+
+            for n in cell.state().iter_ones().take_while(|&n| n < N) {
+                chunk[1 + n / 8] |= 1 << (n % 8);
+            }
+        }
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let width = N.div_ceil(8);
+
+        match bytes.first() {
+            Some(&FORMAT_VERSION) => {}
+            Some(&version) => return Err(DecodeError::UnsupportedVersion(version)),
+            None => return Err(DecodeError::InvalidSize(0)),
+        }
+
+        if bytes.len() != 1 + S * (1 + width) {
+            return Err(DecodeError::InvalidSize(bytes.len()));
+        }
+
+        let mut state = [Cell::default(); S];
+
+        for (i, chunk) in bytes[1..].chunks(1 + width).enumerate() {
+            let mut bits = BitArray::<A>::ZERO;
+
+            for (k, &byte) in chunk[1..].iter().enumerate() {
+                for b in (0..8).filter(|b| byte & (1 << b) != 0) {
+                    let n = k * 8 + b;
+                    if n >= N {
+                        return Err(DecodeError::InvalidCell(i));
+                    }
+                    bits.set(n, true);
+                }
+            }
+
+            let value = match bits.count_ones() {
+                1 => bits.first_one(),
+                _ => None,
+            };
+
+            state[i] = match (chunk[0], value) {
+                (UNKNOWN, _) => Cell::unknown(bits),
+                (REDUCED, Some(n)) => Cell::reduced(n),
+                (COLLAPSED, Some(n)) => Cell::collapsed(n),
+                _ => return Err(DecodeError::InvalidCell(i)),
+            };
+        }
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATES: usize = 2;
+    type TestCell = Cell<u8, STATES>;
+    type TestBoard = [TestCell; 4];
+
+    fn char_map(c: char) -> Option<TestCell> {
+        match c {
+            '.' => Some(Cell::default()),
+            '0' => Some(Cell::reduced(0)),
+            '1' => Some(Cell::reduced(1)),
+            _ => None,
+        }
+    }
+
+    fn cell_map(i: usize, cell: &TestCell) -> String {
+        let c = match cell.value() {
+            Some(n) => n.to_string(),
+            None => String::from("."),
+        };
+
+        if i % 2 == 1 {
+            c + "\n"
+        } else {
+            c
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let raw = "0.\n.1\n";
+        let board = TestBoard::from_chars(raw, char_map).unwrap();
+        assert!(board[0].is_reduced() && board[1].is_unknown());
+        assert_eq!(board.to_string(cell_map), raw);
+    }
+
+    #[test]
+    fn parse_invalid_size() {
+        let actual = TestBoard::from_chars("0.1", char_map).err();
+        assert_eq!(actual, Some(ParseError::InvalidSize(3)));
+    }
+
+    #[test]
+    fn packed_round_trip() {
+        let board: TestBoard = [
+            Cell::default(),
+            Cell::reduced(1),
+            Cell::collapsed(0),
+            Cell::unknown(BitArray::new(0b01)),
+        ];
+        let bytes = board.to_bytes();
+        assert_eq!(bytes, [FORMAT_VERSION, 0, 0b11, 1, 0b10, 2, 0b01, 0, 0b01]);
+        assert_eq!(TestBoard::from_bytes(&bytes), Ok(board));
+    }
+
+    #[test]
+    fn unpack_invalid() {
+        let mut bytes = [TestCell::default(); 4].to_bytes();
+        assert_eq!(
+            TestBoard::from_bytes(&bytes[..4]),
+            Err(DecodeError::InvalidSize(4))
+        );
+
+        bytes[3] = REDUCED;
+        assert_eq!(
+            TestBoard::from_bytes(&bytes),
+            Err(DecodeError::InvalidCell(1))
+        );
+
+        bytes[3] = UNKNOWN;
+        bytes[4] = 0b100;
+        assert_eq!(
+            TestBoard::from_bytes(&bytes),
+            Err(DecodeError::InvalidCell(1))
+        );
+
+        bytes[0] = FORMAT_VERSION + 1;
+        assert_eq!(
+            TestBoard::from_bytes(&bytes),
+            Err(DecodeError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_input() {
+        let actual = TestBoard::from_chars("0.\n2.", char_map).err();
+        assert_eq!(actual, Some(ParseError::InvalidInput(2, '2')));
+    }
+}
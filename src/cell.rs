@@ -1,17 +1,88 @@
+use alloc::{borrow::ToOwned, vec, vec::Vec};
 use bitvec::{array::BitArray, order::Lsb0, view::BitViewSized};
-use rand::{
-    distributions::WeightedError,
-    prelude::{SliceRandom, StdRng},
-};
-use std::fmt::Debug;
+use core::fmt::Debug;
+use rand::{distributions::WeightedError, prelude::SliceRandom, Rng};
 
 /// A BitArray where each 1 represnts a state that the cell could be in
 pub type CellState<A> = BitArray<A, Lsb0>;
 
+/// The states removed from a cell, expressed as whichever mask is natural
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduction<A: BitViewSized> {
+    /// Each 1 represents a state the cell cannot be in
+    Deny(CellState<A>),
+    /// Each 1 represents a state the cell can be in, every other state is
+    /// removed
+    Allow(CellState<A>),
+    /// The cell must be the given state, every other state is removed. A
+    /// state the cell can't be in leaves it without any possible states
+    Force(usize),
+}
+
+impl<A: BitViewSized> Reduction<A> {
+    /// The mask of states the cell cannot be in
+    pub fn into_deny(self) -> CellState<A> {
+        match self {
+            Self::Deny(mask) => mask,
+            Self::Allow(mask) => !mask,
+            Self::Force(n) => {
+                let mut mask = CellState::ZERO;
+                if let Some(mut bit) = mask.get_mut(n) {
+                    *bit = true;
+                }
+                !mask
+            }
+        }
+    }
+}
+
+/// A bare mask is a deny-mask, matching the original reducer contract
+impl<A: BitViewSized> From<CellState<A>> for Reduction<A> {
+    fn from(mask: CellState<A>) -> Self {
+        Self::Deny(mask)
+    }
+}
+
+/// The outcome of reducing a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReduceResult<C> {
+    /// No possible states were removed, including when the cell was already
+    /// known
+    Unchanged(C),
+    /// Some possible states were removed, leaving at least one
+    Changed(C),
+    /// Every possible state was removed
+    Contradiction,
+}
+
+impl<C> ReduceResult<C> {
+    pub fn is_contradiction(&self) -> bool {
+        matches!(self, Self::Contradiction)
+    }
+
+    pub fn is_changed(&self) -> bool {
+        matches!(self, Self::Changed(_))
+    }
+
+    /// The reduced cell, or `None` if every possible state was removed
+    pub fn cell(self) -> Option<C> {
+        match self {
+            Self::Unchanged(cell) | Self::Changed(cell) => Some(cell),
+            Self::Contradiction => None,
+        }
+    }
+}
+
 /// A function which returns the weight associated with a given state
 pub type Weights = fn(&usize) -> usize;
 
-#[derive(Clone, Copy)]
+/// Cells are equal when both their variant and state match, so a reduced
+/// cell never equals a collapsed cell of the same value. See `same_state` to
+/// compare only the possible states
+///
+/// The storage `A` must hold at least `N` bits. For more states than fit in
+/// an integer use an array, `Cell<[u64; 4], 256>` has 256 states
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Cell<A: BitViewSized + Clone + Debug, const N: usize> {
     Unknown(CellState<A>),
     Reduced(CellState<A>, usize),
@@ -19,7 +90,7 @@ pub enum Cell<A: BitViewSized + Clone + Debug, const N: usize> {
 }
 
 impl<A: BitViewSized + Clone + Debug, const N: usize> Debug for Cell<A, N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         match self {
             Self::Unknown(state) => writeln!(f, "{}", state)?,
             Self::Reduced(_, n) => writeln!(f, "({})", n)?,
@@ -29,6 +100,24 @@ impl<A: BitViewSized + Clone + Debug, const N: usize> Debug for Cell<A, N> {
     }
 }
 
+/// Writes unknown cells as `?{010}`, one digit for each of the `N` states in
+/// index order, reduced cells as `(n)`, and collapsed cells as `[n]`
+impl<A: BitViewSized + Clone + Debug, const N: usize> core::fmt::Display for Cell<A, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::Unknown(state) => {
+                write!(f, "?{{")?;
+                for bit in state.iter().take(N) {
+                    write!(f, "{}", *bit as u8)?;
+                }
+                write!(f, "}}")
+            }
+            Self::Reduced(_, n) => write!(f, "({})", n),
+            Self::Collapsed(_, n) => write!(f, "[{}]", n),
+        }
+    }
+}
+
 impl<A: BitViewSized + Clone + Debug, const N: usize> Default for Cell<A, N> {
     fn default() -> Self {
         Self::Unknown({
@@ -42,46 +131,122 @@ impl<A: BitViewSized + Clone + Debug, const N: usize> Default for Cell<A, N> {
 }
 
 impl<A: BitViewSized + Clone + Debug, const N: usize> Cell<A, N> {
-    /// Takes a BitArray where each 1 represents a state the cell cannot be in
-    /// and removes those states from the current cell
-    pub fn reduce(self, reduction: CellState<A>) -> Option<Self> {
+    /// Removes the states denied by `reduction` from the current cell, a bare
+    /// BitArray is treated as a deny-mask where each 1 represents a state the
+    /// cell cannot be in. A reduction which removes nothing returns the cell
+    /// without reclassifying its states
+    pub fn reduce(self, reduction: impl Into<Reduction<A>>) -> ReduceResult<Self> {
         match self {
-            Self::Unknown(state) => {
-                let state = state & !reduction;
-                let possibilities = state
-                    .iter()
-                    .by_vals()
-                    .enumerate()
-                    .take(N)
-                    .filter(|&(_, x)| x)
-                    .map(|(n, _)| n)
-                    .collect::<Vec<usize>>();
-
-                match possibilities.split_first() {
-                    Some((&h, &[])) => Some(Self::Reduced(state, h)),
-                    Some(_) => Some(Self::Unknown(state)),
-                    None => None,
+            Self::Unknown(mut state) => {
+                let mut denied = reduction.into().into_deny();
+                denied &= state.as_bitslice();
+
+                if denied.not_any() {
+                    return ReduceResult::Unchanged(Self::Unknown(state));
+                }
+
+                state &= !denied;
+
+                match Self::from_state(state) {
+                    Some(cell) => ReduceResult::Changed(cell),
+                    None => ReduceResult::Contradiction,
                 }
             }
-            cell => Some(cell),
+            cell => ReduceResult::Unchanged(cell),
         }
     }
 
+    /// Folds several deny-masks into one before reducing the cell a single
+    /// time, for constraints whose masks arrive separately
+    pub fn reduce_many(self, masks: impl IntoIterator<Item = CellState<A>>) -> ReduceResult<Self> {
+        let denied = masks
+            .into_iter()
+            .fold(CellState::ZERO, |acc: CellState<A>, mask| acc | mask);
+        self.reduce(denied)
+    }
+
+    /// Takes a BitArray where each 1 represents a state the cell can be in
+    /// and removes every other state from the current cell
+    pub fn intersect(self, allowed: CellState<A>) -> ReduceResult<Self> {
+        self.reduce(Reduction::Allow(allowed))
+    }
+
+    /// Combines the possible states of both cells, the dual of `intersect`.
+    /// The result is classified like `from_state`, so merging cells with the
+    /// same single state gives a reduced cell
+    pub fn merge(self, other: Self) -> Self {
+        let state = self.state() | other.state();
+        Self::from_state(state.clone()).unwrap_or(Self::Unknown(state))
+    }
+
     /// Randomly selects a possible state
-    pub fn observe(self, weights: Weights, rng: &mut StdRng) -> Result<Self, WeightedError> {
+    pub fn observe(self, weights: Weights, rng: &mut impl Rng) -> Result<Self, WeightedError> {
+        self.observe_value(weights, rng).map(|(cell, _)| cell)
+    }
+
+    /// Randomly selects a possible state, returning the chosen state along
+    /// with the cell. A known cell returns its own value
+    pub fn observe_value(
+        self,
+        weights: Weights,
+        rng: &mut impl Rng,
+    ) -> Result<(Self, usize), WeightedError> {
+        match self {
+            Self::Unknown(state) => {
+                let n = *state
+                    .iter_ones()
+                    .collect::<Vec<usize>>()
+                    .choose_weighted(rng, weights)?;
+                Ok((Self::reduced(n), n))
+            }
+            Self::Reduced(_, n) | Self::Collapsed(_, n) => Ok((self, n)),
+        }
+    }
+
+    /// Randomly selects a possible state, where each state `n` is chosen in
+    /// proportion to `weight(n)`
+    pub fn observe_by(
+        self,
+        weight: impl Fn(usize) -> f64,
+        rng: &mut impl Rng,
+    ) -> Result<Self, WeightedError> {
         match self {
             Self::Unknown(state) => state
                 .iter_ones()
                 .collect::<Vec<usize>>()
-                .choose_weighted(rng, weights)
+                .choose_weighted(rng, |&n| weight(n))
                 .map(ToOwned::to_owned)
                 .map(Self::reduced),
-            // Self::Reduced(state, n) => Ok(Self::Collapsed(state, n)),
-            // Self::Collapsed(_, _) => Ok(self),
             cell => Ok(cell),
         }
     }
 
+    /// The probability of observing each possible state under `weights`,
+    /// empty when no possible state has a positive weight
+    pub fn distribution(&self, weights: Weights) -> Vec<(usize, f64)> {
+        if let Some(n) = self.value() {
+            return vec![(n, 1.0)];
+        }
+
+        let weighted = self.weight_vector(weights);
+        let sum = weighted.iter().map(|&(_, w)| w as f64).sum::<f64>();
+
+        if sum == 0.0 {
+            return vec![];
+        }
+
+        weighted
+            .into_iter()
+            .map(|(n, w)| (n, w as f64 / sum))
+            .collect()
+    }
+
+    /// The raw weight of each possible state, for sampling the cell outside
+    /// of the solver
+    pub fn weight_vector(&self, weights: Weights) -> Vec<(usize, usize)> {
+        self.state().iter_ones().map(|n| (n, weights(&n))).collect()
+    }
+
     /// Updates the result for an fully collapsed cell
     pub fn collapse(self) -> Self {
         match self {
@@ -90,36 +255,118 @@ impl<A: BitViewSized + Clone + Debug, const N: usize> Cell<A, N> {
         }
     }
 
-    /// The number of possible states in the cell's superposition
+    /// Collapses the cell straight to `value` without observing it, or `None`
+    /// if `value` is not one of the cell's possible states
+    pub fn collapse_to(self, value: usize) -> Option<Self> {
+        if value < N && self.state()[value] {
+            Some(Self::collapsed(value))
+        } else {
+            None
+        }
+    }
+
+    /// The number of possible states in the cell's superposition, 0 for
+    /// collapsed cells and contradictions
     pub fn entropy(&self) -> usize {
         match self {
-            Self::Unknown(state) => state.count_ones(),
+            Self::Unknown(state) => state[..N].count_ones(),
             Self::Reduced(_, _) => 1,
             Self::Collapsed(_, _) => 0,
         }
     }
 
-    pub fn is_unknown(&self) -> bool {
-        match self {
-            Self::Unknown(_) => true,
-            _ => false,
+    /// The number of bits needed to count the cell's possible states, an
+    /// integer approximation of `log2(entropy) + 1` which orders cells like
+    /// `entropy` would, but coarsely. It's computed from `entropy` so is no
+    /// cheaper to find, but saves floating point work when cells are bucketed
+    /// by the magnitude of their entropy
+    pub fn entropy_bits(&self) -> u32 {
+        usize::BITS - self.entropy().leading_zeros()
+    }
+
+    /// The Shannon entropy of the cell's superposition when each possible
+    /// state is weighted by `weights`
+    #[cfg(feature = "std")]
+    pub fn shannon_entropy(&self, weights: Weights) -> f64 {
+        let Self::Unknown(state) = self else {
+            return 0.0;
+        };
+
+        let (sum, sum_log) = state
+            .iter_ones()
+            .map(|n| weights(&n) as f64)
+            .filter(|&w| w > 0.0)
+            .fold((0.0, 0.0), |(sum, sum_log), w| {
+                (sum + w, sum_log + w * w.ln())
+            });
+
+        if sum == 0.0 {
+            return 0.0;
         }
+
+        sum.ln() - sum_log / sum
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
     }
 
     pub fn is_reduced(&self) -> bool {
-        match self {
-            Self::Reduced(_, _) => true,
-            _ => false,
-        }
+        matches!(self, Self::Reduced(_, _))
     }
 
     pub fn is_collapsed(&self) -> bool {
+        matches!(self, Self::Collapsed(_, _))
+    }
+
+    /// Whether the cell is unknown but has no possible states left, which
+    /// can only be observed by failing
+    pub fn is_contradiction(&self) -> bool {
         match self {
-            Self::Collapsed(_, _) => true,
+            Self::Unknown(state) => state[..N].not_any(),
             _ => false,
         }
     }
 
+    /// Whether both cells could be in the same states, regardless of variant
+    pub fn same_state(&self, other: &Self) -> bool {
+        self.state() == other.state()
+    }
+
+    /// Classifies a BitArray where each 1 represents a state the cell could
+    /// be in, returning `None` when there are no possible states
+    pub fn from_state(state: CellState<A>) -> Option<Self> {
+        let mut state = state;
+        state[N..].fill(false);
+
+        let mut possibilities = state.iter_ones();
+        match (possibilities.next(), possibilities.next()) {
+            (None, _) => None,
+            (Some(n), None) => Some(Self::Reduced(state, n)),
+            (Some(_), Some(_)) => Some(Self::Unknown(state)),
+        }
+    }
+
+    /// A cell which can only be one of `values`, reduced when there is only
+    /// one, such as to seed a region of a board with a few tiles
+    /// ```
+    /// use wave_function_collapse::cell::Cell;
+    ///
+    /// let cell = Cell::<u8, 4>::restrict_to(&[1, 3]);
+    /// assert!(cell.state().iter_ones().eq([1, 3]));
+    /// assert_eq!(Cell::<u8, 4>::restrict_to(&[2]), Cell::reduced(2));
+    /// ```
+    ///
+    /// Panics if none of the values are below `N`
+    pub fn restrict_to(values: &[usize]) -> Self {
+        let mut state = BitArray::ZERO;
+        for &n in values.iter().filter(|&&n| n < N) {
+            state.set(n, true);
+        }
+
+        Self::from_state(state).expect("A cell must be restricted to at least one state")
+    }
+
     pub fn unknown(state: CellState<A>) -> Self {
         Self::Unknown(state)
     }
@@ -163,11 +410,48 @@ impl<A: BitViewSized + Clone + Debug, const N: usize> Cell<A, N> {
             Self::Collapsed(state, _) => state.clone(),
         }
     }
+
+    /// Packs the cell into a single integer for compact dumps, the negated
+    /// bitmask of possible states for unknown cells and the value otherwise.
+    /// A contradiction packs to `i64::MIN` so it can't be mistaken for a
+    /// collapsed cell, and unpacks to `None`
+    /// ```
+    /// use wave_function_collapse::cell::Cell;
+    ///
+    /// assert_eq!(Cell::<u8, 3>::default().as_i64(), -0b111);
+    /// assert_eq!(Cell::<u8, 3>::reduced(2).as_i64(), 2);
+    /// ```
+    ///
+    /// Panics if `N` is more than 62
+    pub fn as_i64(&self) -> i64 {
+        assert!(N <= 62, "{} states do not fit in an i64", N);
+        match self {
+            Self::Unknown(_) if self.is_contradiction() => i64::MIN,
+            Self::Unknown(state) => -(state.iter_ones().fold(0, |acc, n| acc | 1 << n)),
+            Self::Reduced(_, n) | Self::Collapsed(_, n) => *n as i64,
+        }
+    }
+
+    /// Unpacks a cell written by `as_i64`. Non-negative values become
+    /// collapsed cells, returning `None` when the value is out of range or
+    /// the bitmask has no possible states
+    pub fn from_i64(packed: i64) -> Option<Self> {
+        if packed >= 0 {
+            return (packed < N as i64).then(|| Self::collapsed(packed as usize));
+        }
+
+        let mask = packed.unsigned_abs();
+        let mut state = BitArray::ZERO;
+        for n in 0..N.min(64) {
+            state.set(n, mask >> n & 1 == 1);
+        }
+        Self::from_state(state)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use rand::{thread_rng, SeedableRng};
+    use rand::{rngs::StdRng, SeedableRng};
 
     use super::*;
 
@@ -185,28 +469,27 @@ mod tests {
     fn reduce_to_none() {
         let reduction = TestCell::default().state();
         let actual = TestCell::default().reduce(reduction);
-        assert!(actual.is_none())
+        assert!(actual.is_contradiction(), "Actual: {:?}", actual)
     }
 
     #[test]
     /// Reduce a cell to many states
     fn reduce_to_many() {
         let reduction = TestCell::reduced(STATES - 1).state();
-        let actual = TestCell::default().reduce(reduction).unwrap();
-        let expected = {
+        let actual = TestCell::default().reduce(reduction).cell().unwrap();
+        let expected = TestCell::unknown({
             let mut bits = State::ZERO;
             for i in 0..(STATES - 1) {
                 bits.set(i, true);
             }
             bits
-        };
+        });
         assert!(
-            actual.state() == expected,
+            actual == expected,
             "Actual: {:?} Expected: {:?}",
             actual,
             expected
         );
-        assert!(actual.is_unknown());
     }
 
     #[test]
@@ -219,21 +502,319 @@ mod tests {
             }
             bits
         };
-        let actual = TestCell::default().reduce(reduction).unwrap();
-        let expected = TestCell::reduced(STATES - 1).state();
+        let actual = TestCell::default().reduce(reduction).cell().unwrap();
+        let expected = TestCell::reduced(STATES - 1);
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn from_state_classifies() {
+        assert!(TestCell::from_state(State::ZERO).is_none());
+        // States beyond N are not possibilities
+        assert!(TestCell::from_state(State::new(0b1000)).is_none());
+
+        let actual = TestCell::from_state(State::new(0b1010)).unwrap();
+        assert!(actual == TestCell::reduced(1), "Actual: {:?}", actual);
+
+        let actual = TestCell::from_state(State::new(0b101)).unwrap();
+        let expected = TestCell::unknown(State::new(0b101));
+        assert!(actual == expected, "Actual: {:?}", actual);
+    }
+
+    #[test]
+    fn display_cells() {
+        use alloc::string::ToString;
+
+        let actual = [
+            TestCell::unknown(State::new(0b010)).to_string(),
+            TestCell::reduced(2).to_string(),
+            TestCell::collapsed(1).to_string(),
+        ];
+        let expected = ["?{010}", "(2)", "[1]"];
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    /// Equality compares variants while `same_state` only compares states
+    fn equality_by_variant() {
+        assert!(TestCell::reduced(1) != TestCell::collapsed(1));
+        assert!(TestCell::reduced(1).same_state(&TestCell::collapsed(1)));
+        assert!(!TestCell::reduced(1).same_state(&TestCell::reduced(2)));
+        assert!(TestCell::default() == TestCell::unknown(State::new(0b111)));
+    }
+
+    #[test]
+    fn reduce_force() {
+        let actual = TestCell::default().reduce(Reduction::Force(2));
+        let expected = ReduceResult::Changed(TestCell::reduced(2));
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+
+        let actual = TestCell::unknown(State::new(0b011)).reduce(Reduction::Force(2));
+        assert!(actual.is_contradiction(), "Actual: {:?}", actual);
+    }
+
+    #[test]
+    /// Allow-masks and deny-masks remove the same states
+    fn reduce_allow_or_deny() {
+        let deny = TestCell::default().reduce(Reduction::Deny(State::new(0b100)));
+        let allow = TestCell::default().reduce(Reduction::Allow(State::new(0b011)));
+        let expected = ReduceResult::Changed(TestCell::unknown(State::new(0b011)));
+        assert!(
+            deny == expected,
+            "Actual: {:?}, Expected: {:?}",
+            deny,
+            expected
+        );
         assert!(
-            actual.state() == expected,
+            allow == expected,
+            "Actual: {:?}, Expected: {:?}",
+            allow,
+            expected
+        );
+    }
+
+    #[test]
+    /// A reduction disjoint from the cell's states leaves it untouched
+    fn reduce_to_same() {
+        let cell = TestCell::unknown(State::new(0b011));
+        let actual = cell.reduce(State::new(0b11111100));
+        let expected = ReduceResult::Unchanged(cell);
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn reduce_many_masks() {
+        let masks = [State::new(0b001), State::new(0b100)];
+        let actual = TestCell::default().reduce_many(masks);
+        let expected = ReduceResult::Changed(TestCell::reduced(1));
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+
+        let actual = TestCell::default().reduce_many([]);
+        let expected = ReduceResult::Unchanged(TestCell::default());
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    /// Reductions report whether they removed any states
+    fn reduce_result_changes() {
+        let unchanged = TestCell::unknown(State::new(0b011)).reduce(State::new(0b100));
+        let changed = TestCell::unknown(State::new(0b011)).reduce(State::new(0b010));
+        let known = TestCell::collapsed(0).reduce(State::new(0b111));
+        assert!(
+            unchanged == ReduceResult::Unchanged(TestCell::unknown(State::new(0b011))),
+            "Actual: {:?}",
+            unchanged
+        );
+        assert!(
+            changed == ReduceResult::Changed(TestCell::reduced(0)),
+            "Actual: {:?}",
+            changed
+        );
+        assert!(
+            known == ReduceResult::Unchanged(TestCell::collapsed(0)),
+            "Actual: {:?}",
+            known
+        );
+    }
+
+    #[test]
+    /// Intersect a cell with a disjoint set of states
+    fn intersect_to_none() {
+        let actual = TestCell::unknown(State::new(0b011)).intersect(State::new(0b100));
+        assert!(actual.is_contradiction(), "Actual: {:?}", actual)
+    }
+
+    #[test]
+    /// Intersect a cell down to one shared state
+    fn intersect_to_one() {
+        let actual = TestCell::unknown(State::new(0b011))
+            .intersect(State::new(0b110))
+            .cell()
+            .unwrap();
+        assert!(actual == TestCell::reduced(1), "Actual: {:?}", actual);
+    }
+
+    #[test]
+    /// Intersect a cell with an allow-mask covering several states
+    fn intersect_to_many() {
+        let actual = TestCell::default()
+            .intersect(State::new(0b101))
+            .cell()
+            .unwrap();
+        let expected = TestCell::unknown(State::new(0b101));
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn collapse_to_possible_state() {
+        let actual = [
+            TestCell::unknown(State::new(0b011)).collapse_to(1),
+            TestCell::unknown(State::new(0b011)).collapse_to(2),
+            TestCell::reduced(2).collapse_to(2),
+            TestCell::collapsed(2).collapse_to(0),
+            TestCell::default().collapse_to(STATES),
+        ];
+        let expected = [
+            Some(TestCell::collapsed(1)),
+            None,
+            Some(TestCell::collapsed(2)),
+            None,
+            None,
+        ];
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn entropy_bits_magnitude() {
+        let actual = [
+            TestCell::collapsed(0).entropy_bits(),
+            TestCell::reduced(0).entropy_bits(),
+            TestCell::unknown(State::new(0b011)).entropy_bits(),
+            TestCell::default().entropy_bits(),
+        ];
+        let expected = [0, 1, 2, 2];
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn shannon_entropy_uniform() {
+        let actual = TestCell::default().shannon_entropy(uniform);
+        let expected = (STATES as f64).ln();
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+        assert!(TestCell::reduced(0).shannon_entropy(uniform) == 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    /// Skewed weights leave less uncertainty than uniform ones
+    fn shannon_entropy_weighted() {
+        fn skewed(n: &usize) -> usize {
+            if *n == 0 {
+                98
+            } else {
+                1
+            }
+        }
+
+        let actual = TestCell::default().shannon_entropy(skewed);
+        assert!(
+            actual > 0.0 && actual < (STATES as f64).ln(),
+            "Actual: {:?}",
+            actual
+        );
+    }
+
+    #[test]
+    fn weight_vector_possible_states() {
+        fn double(n: &usize) -> usize {
+            n * 2
+        }
+
+        let actual = TestCell::unknown(State::new(0b110)).weight_vector(double);
+        let expected = vec![(1, 2), (2, 4)];
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+        assert!(TestCell::collapsed(2).weight_vector(double) == vec![(2, 4)]);
+    }
+
+    #[test]
+    fn distribution_weighted() {
+        fn linear(n: &usize) -> usize {
+            *n
+        }
+
+        let actual = TestCell::default().distribution(linear);
+        let expected = vec![(0, 0.0), (1, 1.0 / 3.0), (2, 2.0 / 3.0)];
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+        assert!(TestCell::reduced(2).distribution(linear) == vec![(2, 1.0)]);
+        assert!(TestCell::reduced(0).distribution(linear) == vec![(0, 1.0)]);
+        assert!(TestCell::unknown(State::new(0b001))
+            .distribution(linear)
+            .is_empty());
+    }
+
+    #[test]
+    fn contradiction_entropy() {
+        let cells = [
+            TestCell::Unknown(State::ZERO),
+            TestCell::Unknown(State::new(0b1000)),
+            TestCell::default(),
+            TestCell::collapsed(0),
+        ];
+        let actual = cells.map(|cell| (cell.entropy(), cell.is_contradiction()));
+        let expected = [(0, true), (0, true), (STATES, false), (0, false)];
+        assert!(
+            actual == expected,
             "Actual: {:?}, Expected: {:?}",
             actual,
             expected
         );
-        assert!(actual.is_reduced());
     }
 
     #[test]
     fn observe_empty_state() {
         let actual = TestCell::Unknown(State::ZERO)
-            .observe(uniform, &mut StdRng::from_rng(thread_rng()).unwrap())
+            .observe(uniform, &mut StdRng::seed_from_u64(0))
             .err();
         let expected = Result::<TestCell, WeightedError>::Err(WeightedError::NoItem).err();
         assert!(
@@ -247,11 +828,11 @@ mod tests {
     #[test]
     fn observe_random_state() {
         let actual = TestCell::default()
-            .observe(uniform, &mut StdRng::from_rng(thread_rng()).unwrap())
+            .observe(uniform, &mut StdRng::seed_from_u64(0))
             .unwrap()
             .value()
             .unwrap();
-        let expected = (0..STATES).map(|i| i).collect::<Vec<usize>>();
+        let expected = (0..STATES).collect::<Vec<usize>>();
         assert!(
             expected.contains(&actual),
             "Actual: {:?}, Expected: {:?}",
@@ -259,4 +840,149 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn observe_value_matches_cell() {
+        let (actual, n) = TestCell::default()
+            .observe_value(uniform, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        let expected = TestCell::reduced(n);
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+
+        let actual = TestCell::Collapsed(State::ZERO, 1)
+            .observe_value(uniform, &mut StdRng::seed_from_u64(0))
+            .unwrap()
+            .1;
+        assert!(actual == 1, "Actual: {:?}, Expected: {:?}", actual, 1);
+    }
+
+    #[test]
+    fn observe_by_weight() {
+        let actual = TestCell::default()
+            .observe_by(|n| (n == 2) as usize as f64, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        let expected = TestCell::reduced(2);
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    /// Array storage holds more states than any integer
+    fn many_states() {
+        type WideCell = Cell<[u64; 5], 300>;
+
+        let mut reduction = CellState::<[u64; 5]>::ZERO;
+        reduction[..300].fill(true);
+        reduction.set(250, false);
+        reduction.set(299, false);
+
+        let actual = WideCell::default().reduce(reduction).cell().unwrap();
+        assert!(actual.is_unknown());
+        assert!(actual.entropy() == 2, "Actual: {:?}", actual);
+
+        let actual = actual
+            .observe(uniform, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert!(
+            actual.value() == Some(250) || actual.value() == Some(299),
+            "Actual: {:?}",
+            actual
+        );
+        assert!(actual.collapse().is_collapsed());
+    }
+
+    #[test]
+    fn merge_reduced_and_unknown() {
+        let actual = TestCell::reduced(0).merge(TestCell::unknown(State::new(0b110)));
+        let expected = TestCell::default();
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+
+        let actual = TestCell::reduced(1).merge(TestCell::unknown(State::new(0b110)));
+        let expected = TestCell::unknown(State::new(0b110));
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+
+        let actual = TestCell::collapsed(2).merge(TestCell::reduced(2));
+        let expected = TestCell::reduced(2);
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn restrict_to_values() {
+        let actual = TestCell::restrict_to(&[2, 0, 2]);
+        let expected = TestCell::unknown(State::new(0b101));
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+
+        let actual = TestCell::restrict_to(&[1, STATES]);
+        let expected = TestCell::reduced(1);
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn restrict_to_nothing() {
+        TestCell::restrict_to(&[]);
+    }
+
+    #[test]
+    fn pack_i64() {
+        for cell in [
+            TestCell::unknown(State::new(0b101)),
+            TestCell::default(),
+            TestCell::collapsed(1),
+        ] {
+            let actual = TestCell::from_i64(cell.as_i64());
+            assert!(
+                actual == Some(cell),
+                "Actual: {:?}, Expected: {:?}",
+                actual,
+                cell
+            );
+        }
+
+        let contradiction = TestCell::unknown(State::ZERO).as_i64();
+        assert!(contradiction == i64::MIN, "Actual: {}", contradiction);
+
+        let actual = [-0b1000, 0, 3, contradiction].map(TestCell::from_i64);
+        let expected = [None, Some(TestCell::collapsed(0)), None, None];
+        assert!(
+            actual == expected,
+            "Actual: {:?}, Expected: {:?}",
+            actual,
+            expected
+        );
+    }
 }
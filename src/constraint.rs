@@ -0,0 +1,72 @@
+use alloc::{boxed::Box, vec::Vec};
+use bitvec::{array::BitArray, view::BitViewSized};
+use core::fmt::Debug;
+
+use crate::{
+    cell::{Cell, CellState, Reduction},
+    solver::StateReducer,
+};
+
+/// A single rule of a constraint problem, several of which can be combined
+/// into one reducer with `SolverBuilder::constraints`. Closures with the same
+/// signature as `forbidden` are constraints too
+pub trait Constraint<A: BitViewSized + Copy + Debug, const N: usize>: Send + Sync {
+    /// The states the ith cell cannot be in given its known neighbors, where
+    /// each 1 represents a forbidden state
+    fn forbidden(&self, neighbors: &[(usize, &Cell<A, N>)], i: usize) -> CellState<A>;
+}
+
+impl<A, const N: usize, F> Constraint<A, N> for F
+where
+    A: BitViewSized + Copy + Debug,
+    F: Fn(&[(usize, &Cell<A, N>)], usize) -> CellState<A> + Send + Sync,
+{
+    fn forbidden(&self, neighbors: &[(usize, &Cell<A, N>)], i: usize) -> CellState<A> {
+        self(neighbors, i)
+    }
+}
+
+/// Combines constraints into a reducer which forbids every state forbidden
+/// by any of them
+pub fn compose<A: BitViewSized + Copy + Debug + 'static, const N: usize>(
+    constraints: Vec<Box<dyn Constraint<A, N>>>,
+) -> StateReducer<A, N> {
+    Box::new(move |neighbors: Vec<(usize, &Cell<A, N>)>, i: usize| {
+        Reduction::Deny(constraints.iter().fold(BitArray::ZERO, |acc, constraint| {
+            acc | constraint.forbidden(&neighbors, i)
+        }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    type TestCell = Cell<u8, 3>;
+
+    /// Forbids the value of the cell before the ith
+    struct NotPrevious;
+
+    impl Constraint<u8, 3> for NotPrevious {
+        fn forbidden(&self, neighbors: &[(usize, &TestCell)], i: usize) -> CellState<u8> {
+            neighbors
+                .iter()
+                .filter(|&&(j, _)| j + 1 == i)
+                .fold(BitArray::ZERO, |acc, (_, cell)| acc | cell.state())
+        }
+    }
+
+    #[test]
+    fn compose_constraints() {
+        let never_two = |_: &[(usize, &TestCell)], _: usize| CellState::<u8>::new(0b100);
+        let reducer = compose(vec![Box::new(NotPrevious), Box::new(never_two)]);
+        let previous = TestCell::collapsed(0);
+        let next = TestCell::collapsed(1);
+
+        let actual = reducer(vec![(0, &previous), (2, &next)], 1).into_deny();
+        assert_eq!(actual.data, 0b101);
+        assert_eq!(compose::<u8, 3>(vec![])(vec![], 1).into_deny().data, 0);
+    }
+}
@@ -0,0 +1,40 @@
+use alloc::vec::Vec;
+
+/// The nodes sharing an edge with the ith node of an undirected graph, in the
+/// order the edges are listed. Wrapping this in a function gives the solver
+/// neighbors for an arbitrary graph rather than a grid
+/// ```
+/// use wave_function_collapse::graph::neighbors_in;
+///
+/// const EDGES: [(usize, usize); 3] = [(0, 1), (1, 2), (2, 0)];
+///
+/// fn neighbors(i: usize) -> Vec<usize> {
+///     neighbors_in(&EDGES, i)
+/// }
+///
+/// assert_eq!(neighbors(1), [0, 2]);
+/// ```
+pub fn neighbors_in(edges: &[(usize, usize)], i: usize) -> Vec<usize> {
+    edges
+        .iter()
+        .filter_map(|&(a, b)| match (a == i, b == i) {
+            (true, false) => Some(b),
+            (false, true) => Some(a),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors_either_end() {
+        let edges = [(0, 1), (2, 0), (1, 2), (3, 3)];
+        assert_eq!(neighbors_in(&edges, 0), [1, 2]);
+        assert_eq!(neighbors_in(&edges, 2), [0, 1]);
+        // A node is never its own neighbor
+        assert_eq!(neighbors_in(&edges, 3), []);
+    }
+}
@@ -0,0 +1,268 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use bitvec::view::BitViewSized;
+use core::fmt::Debug;
+
+use crate::cell::{Cell, Reduction};
+
+/// A function which returns cells adjacent to a given index along with the
+/// direction of each from the given cell
+pub type DirectedNeighbors = fn(usize) -> Vec<(usize, Direction)>;
+
+/// A reducer which is given the direction of each known neighbor, see
+/// `SolverBuilder::directed`
+pub type DirectedReducer<A, const N: usize> =
+    Box<dyn Fn(Vec<(usize, Direction, &Cell<A, N>)>, usize) -> Reduction<A> + Send + Sync>;
+
+/// A function which scales the weight of a state given the directions of a
+/// cell's known neighbors, see `SolverBuilder::directional_weights`
+pub type DirectionalWeights = fn(usize, &[Direction]) -> f64;
+
+/// The direction of a neighbor in a 4-connected grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub const ALL: [Self; 4] = [Self::Left, Self::Right, Self::Up, Self::Down];
+
+    /// The direction of the jth cell from the ith in a grid `row_len` cells
+    /// wide, or `None` if the cells aren't directly adjacent
+    /// ```
+    /// use wave_function_collapse::grid::Direction;
+    ///
+    /// assert_eq!(Direction::between(5, 4, 4), Some(Direction::Left));
+    /// assert_eq!(Direction::between(5, 1, 4), Some(Direction::Up));
+    /// // The end of the previous row doesn't wrap around
+    /// assert_eq!(Direction::between(4, 3, 4), None);
+    /// ```
+    pub fn between(i: usize, j: usize, row_len: usize) -> Option<Self> {
+        if j + 1 == i && !i.is_multiple_of(row_len) {
+            Some(Self::Left)
+        } else if j == i + 1 && !j.is_multiple_of(row_len) {
+            Some(Self::Right)
+        } else if j + row_len == i {
+            Some(Self::Up)
+        } else if j == i + row_len {
+            Some(Self::Down)
+        } else {
+            None
+        }
+    }
+
+    /// The direction of the jth cell from the ith like `between`, but in a
+    /// grid `width` cells wide and `height` cells tall whose edges wrap around
+    /// to the opposite side. Directions are ambiguous on a torus less than 3
+    /// cells wide or tall
+    /// ```
+    /// use wave_function_collapse::grid::Direction;
+    ///
+    /// assert_eq!(Direction::between_wrapping(4, 7, 4, 3), Some(Direction::Left));
+    /// assert_eq!(Direction::between_wrapping(1, 9, 4, 3), Some(Direction::Up));
+    /// ```
+    pub fn between_wrapping(i: usize, j: usize, width: usize, height: usize) -> Option<Self> {
+        let (x, y) = (i % width, i / width);
+
+        if j == y * width + (x + width - 1) % width {
+            Some(Self::Left)
+        } else if j == y * width + (x + 1) % width {
+            Some(Self::Right)
+        } else if j == (y + height - 1) % height * width + x {
+            Some(Self::Up)
+        } else if j == (y + 1) % height * width + x {
+            Some(Self::Down)
+        } else {
+            None
+        }
+    }
+
+    /// The direction pointing back the way this one came
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+        }
+    }
+}
+
+/// The cells directly left, right, above, and below the ith in a grid `width`
+/// cells wide and `height` cells tall, along with their directions
+pub fn grid_neighbors(i: usize, width: usize, height: usize) -> Vec<(usize, Direction)> {
+    let (x, y) = (i % width, i / width);
+    let mut neighbors = vec![];
+
+    if x > 0 {
+        neighbors.push((i - 1, Direction::Left));
+    }
+
+    if x + 1 < width {
+        neighbors.push((i + 1, Direction::Right));
+    }
+
+    if y > 0 {
+        neighbors.push((i - width, Direction::Up));
+    }
+
+    if y + 1 < height {
+        neighbors.push((i + width, Direction::Down));
+    }
+
+    neighbors
+}
+
+/// The cells on each side of the ith in a grid `width` cells wide and
+/// `height` cells tall whose edges wrap around to the opposite side, so boards
+/// tile seamlessly when repeated. Every cell has a neighbor on all four sides
+pub fn torus_neighbors(i: usize, width: usize, height: usize) -> Vec<(usize, Direction)> {
+    let (x, y) = (i % width, i / width);
+
+    vec![
+        (y * width + (x + width - 1) % width, Direction::Left),
+        (y * width + (x + 1) % width, Direction::Right),
+        ((y + height - 1) % height * width + x, Direction::Up),
+        ((y + 1) % height * width + x, Direction::Down),
+    ]
+}
+
+/// The known neighbors of a cell in a 4-connected grid, as given to the
+/// reducer of `SolverBuilder::grid2d`. A side is `None` when the neighbor is
+/// unknown or off the edge of the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NeighborsByDir<'a, A: BitViewSized + Clone + Debug, const N: usize> {
+    pub left: Option<&'a Cell<A, N>>,
+    pub right: Option<&'a Cell<A, N>>,
+    pub up: Option<&'a Cell<A, N>>,
+    pub down: Option<&'a Cell<A, N>>,
+}
+
+impl<'a, A: BitViewSized + Clone + Debug, const N: usize> NeighborsByDir<'a, A, N> {
+    /// Groups neighbors by their direction, a later neighbor replaces an
+    /// earlier one in the same direction
+    pub fn from_directed(neighbors: Vec<(usize, Direction, &'a Cell<A, N>)>) -> Self {
+        let mut by_dir = Self {
+            left: None,
+            right: None,
+            up: None,
+            down: None,
+        };
+
+        for (_, direction, cell) in neighbors {
+            match direction {
+                Direction::Left => by_dir.left = Some(cell),
+                Direction::Right => by_dir.right = Some(cell),
+                Direction::Up => by_dir.up = Some(cell),
+                Direction::Down => by_dir.down = Some(cell),
+            }
+        }
+
+        by_dir
+    }
+
+    /// The neighbor in `direction`, if it's known
+    pub fn get(&self, direction: Direction) -> Option<&'a Cell<A, N>> {
+        match direction {
+            Direction::Left => self.left,
+            Direction::Right => self.right,
+            Direction::Up => self.up,
+            Direction::Down => self.down,
+        }
+    }
+
+    /// Each known neighbor along with its direction
+    pub fn iter(&self) -> impl Iterator<Item = (Direction, &'a Cell<A, N>)> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| self.get(direction).map(|cell| (direction, cell)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn between_neighbors() {
+        let row_len = 3;
+        let actual = [1, 3, 5, 7, 0, 8]
+            .map(|j| Direction::between(4, j, row_len))
+            .to_vec();
+        let expected = [
+            Some(Direction::Up),
+            Some(Direction::Left),
+            Some(Direction::Right),
+            Some(Direction::Down),
+            None,
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    /// Neighbors at the edges of rows don't wrap onto the next row
+    fn between_row_edges() {
+        assert_eq!(Direction::between(3, 2, 3), None);
+        assert_eq!(Direction::between(2, 3, 3), None);
+        assert_eq!(Direction::between(2, 1, 3), Some(Direction::Left));
+    }
+
+    #[test]
+    fn grid_corner_and_middle() {
+        assert_eq!(
+            grid_neighbors(0, 3, 2),
+            [(1, Direction::Right), (3, Direction::Down)]
+        );
+        assert_eq!(
+            grid_neighbors(4, 3, 2),
+            [
+                (3, Direction::Left),
+                (5, Direction::Right),
+                (1, Direction::Up)
+            ]
+        );
+    }
+
+    #[test]
+    fn torus_wraps_edges() {
+        assert_eq!(
+            torus_neighbors(0, 3, 2),
+            [
+                (2, Direction::Left),
+                (1, Direction::Right),
+                (3, Direction::Up),
+                (3, Direction::Down)
+            ]
+        );
+
+        for i in 0..12 {
+            for (j, direction) in torus_neighbors(i, 4, 3) {
+                assert_eq!(Direction::between_wrapping(i, j, 4, 3), Some(direction));
+                assert_eq!(
+                    Direction::between_wrapping(j, i, 4, 3),
+                    Some(direction.opposite())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn group_by_direction() {
+        let cell = Cell::<u8, 2>::reduced(1);
+        let by_dir = NeighborsByDir::from_directed(vec![(3, Direction::Up, &cell)]);
+        assert_eq!(by_dir.up, Some(&cell));
+        assert_eq!(by_dir.left, None);
+        assert_eq!(by_dir.iter().collect::<Vec<_>>(), [(Direction::Up, &cell)]);
+    }
+
+    #[test]
+    fn opposite_directions() {
+        for direction in Direction::ALL {
+            assert_ne!(direction.opposite(), direction);
+            assert_eq!(direction.opposite().opposite(), direction);
+        }
+    }
+}
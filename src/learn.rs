@@ -0,0 +1,226 @@
+use alloc::{vec, vec::Vec};
+use bitvec::{array::BitArray, vec::BitVec, view::BitViewSized};
+use core::fmt::Debug;
+
+use crate::{solver::StateReducer, tiles::TileRules};
+
+/// The pairs of tiles seen next to each other in a sample. `right[t]` holds
+/// every tile seen directly to the right of the tth tile, and `below[t]` every
+/// tile seen directly below it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdjacencyRules {
+    right: Vec<BitVec>,
+    below: Vec<BitVec>,
+}
+
+/// Scans a solved grid of tiles, `width` tiles per row, and records which
+/// pairs of tiles appear next to each other in each direction. Tiles are
+/// numbered from 0, so the rules cover every tile up to the largest in the
+/// sample
+/// ```
+/// use wave_function_collapse::learn::adjacency_from_sample;
+///
+/// let rules = adjacency_from_sample(&[0, 1, 1, 0], 2);
+/// assert!(rules.allows_right(0, 1) && !rules.allows_right(0, 0));
+/// assert!(rules.allows_below(0, 1) && !rules.allows_below(1, 1));
+/// ```
+///
+/// Panics if the length of `sample` is not a multiple of `width`
+pub fn adjacency_from_sample(sample: &[usize], width: usize) -> AdjacencyRules {
+    assert!(
+        width > 0 && sample.len().is_multiple_of(width),
+        "A sample of {} tiles cannot be {} tiles wide",
+        sample.len(),
+        width
+    );
+
+    let tiles = sample.iter().max().map_or(0, |&t| t + 1);
+    let mut rules = AdjacencyRules {
+        right: vec![BitVec::repeat(false, tiles); tiles],
+        below: vec![BitVec::repeat(false, tiles); tiles],
+    };
+
+    for (i, &tile) in sample.iter().enumerate() {
+        if (i + 1) % width != 0 {
+            rules.right[tile].set(sample[i + 1], true);
+        }
+
+        if let Some(&below) = sample.get(i + width) {
+            rules.below[tile].set(below, true);
+        }
+    }
+
+    rules
+}
+
+/// Counts how many times each tile appears in a sample, indexed by tile, so
+/// generated boards can reproduce the sample's distribution of tiles. Since
+/// `Weights` is a function pointer the counts must be looked up from a
+/// function, such as by copying them into a table
+/// ```
+/// use wave_function_collapse::learn::weights_from_sample;
+///
+/// assert_eq!(weights_from_sample(&[0, 2, 2, 0, 2]), [2, 0, 3]);
+///
+/// const WEIGHTS: [usize; 3] = [2, 0, 3];
+/// fn weights(n: &usize) -> usize {
+///     WEIGHTS[*n]
+/// }
+/// ```
+pub fn weights_from_sample(sample: &[usize]) -> Vec<usize> {
+    let tiles = sample.iter().max().map_or(0, |&t| t + 1);
+    let mut weights = vec![0; tiles];
+
+    for &tile in sample {
+        weights[tile] += 1;
+    }
+
+    weights
+}
+
+impl AdjacencyRules {
+    /// The number of tiles the rules cover
+    pub fn tiles(&self) -> usize {
+        self.right.len()
+    }
+
+    /// Whether tile `b` may sit directly to the right of tile `a`
+    pub fn allows_right(&self, a: usize, b: usize) -> bool {
+        self.right.get(a).is_some_and(|right| right[b])
+    }
+
+    /// Whether tile `b` may sit directly below tile `a`
+    pub fn allows_below(&self, a: usize, b: usize) -> bool {
+        self.below.get(a).is_some_and(|below| below[b])
+    }
+
+    /// Converts the rules into deny-masks for each direction, where a cell
+    /// may only be a tile which was seen next to its neighbor in the sample.
+    ///
+    /// Panics if there are more tiles than bits in `A`
+    pub fn to_tile_rules<A: BitViewSized + Copy>(&self) -> TileRules<A> {
+        let tiles = self.tiles();
+        let masks = |allows: &dyn Fn(usize, usize) -> bool| {
+            (0..tiles)
+                .map(|neighbor| {
+                    let mut bits = BitArray::ZERO;
+                    for n in 0..tiles {
+                        bits.set(n, !allows(neighbor, n));
+                    }
+                    bits
+                })
+                .collect()
+        };
+
+        TileRules {
+            left: masks(&|neighbor, n| self.allows_right(neighbor, n)),
+            right: masks(&|neighbor, n| self.allows_right(n, neighbor)),
+            top: masks(&|neighbor, n| self.allows_below(neighbor, n)),
+            bottom: masks(&|neighbor, n| self.allows_below(n, neighbor)),
+        }
+    }
+
+    /// Converts the rules into a reducer for a grid `row_len` cells wide, so
+    /// a solver can generate boards resembling the sample
+    /// ```
+    /// use wave_function_collapse::{learn::adjacency_from_sample, solver::SolverBuilder};
+    ///
+    /// const WIDTH: usize = 4;
+    ///
+    /// fn neighbors(i: usize) -> Vec<usize> {
+    ///     let mut neighbors = vec![];
+    ///     if i % WIDTH > 0 { neighbors.push(i - 1); }
+    ///     if i % WIDTH < WIDTH - 1 { neighbors.push(i + 1); }
+    ///     if i >= WIDTH { neighbors.push(i - WIDTH); }
+    ///     if i + WIDTH < WIDTH * WIDTH { neighbors.push(i + WIDTH); }
+    ///     neighbors
+    /// }
+    ///
+    /// // Checkerboards of two tiles
+    /// let rules = adjacency_from_sample(&[0, 1, 1, 0], 2);
+    /// let mut solver = SolverBuilder::<u8, 2, { WIDTH * WIDTH }>::new(
+    ///     neighbors,
+    ///     rules.into_reducer(WIDTH),
+    /// )
+    /// .build().unwrap();
+    /// solver.solve().unwrap();
+    /// ```
+    ///
+    /// Panics if there are more tiles than bits in `A`
+    pub fn into_reducer<A: BitViewSized + Copy + Debug + Send + Sync + 'static, const N: usize>(
+        self,
+        row_len: usize,
+    ) -> StateReducer<A, N> {
+        self.to_tile_rules().into_reducer(row_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cell::CellState,
+        tiles::{Sides, TileRules},
+    };
+
+    #[test]
+    fn learn_stripes() {
+        // Vertical stripes of 0 and 1 separated by 2
+        let sample = [0, 2, 1, 2, 0, 2, 1, 2, 0, 2, 1, 2];
+        let rules = adjacency_from_sample(&sample, 4);
+        assert_eq!(rules.tiles(), 3);
+
+        assert!(rules.allows_right(0, 2) && rules.allows_right(2, 1));
+        assert!(!rules.allows_right(0, 1) && !rules.allows_right(2, 2));
+        assert!((0..3).all(|t| rules.allows_below(t, t)));
+        assert!(!rules.allows_below(0, 1));
+    }
+
+    #[test]
+    /// A sample of pipes never allows a pair `TileRules` would deny, since
+    /// every pair in the sample connects properly
+    fn learn_pipes() {
+        let tiles = [
+            Sides::NONE,
+            Sides::RIGHT | Sides::BOTTOM,
+            Sides::LEFT | Sides::BOTTOM,
+            Sides::TOP | Sides::BOTTOM,
+            Sides::TOP | Sides::RIGHT,
+            Sides::TOP | Sides::LEFT,
+        ];
+        // ┌┐
+        // ││
+        // └┘
+        let sample = [0, 0, 0, 0, 0, 1, 2, 0, 0, 3, 3, 0, 0, 4, 5, 0, 0, 0, 0, 0];
+        let learned = adjacency_from_sample(&sample, 4).to_tile_rules::<u8>();
+        let expected = TileRules::<u8>::new(&tiles);
+        let stricter = |learned: &[CellState<u8>], expected: &[CellState<u8>]| {
+            learned
+                .iter()
+                .zip(expected)
+                .all(|(learned, expected)| (*expected & !*learned).not_any())
+        };
+
+        assert!(stricter(&learned.left, &expected.left));
+        assert!(stricter(&learned.right, &expected.right));
+        assert!(stricter(&learned.top, &expected.top));
+        assert!(stricter(&learned.bottom, &expected.bottom));
+        // The corner is only ever seen with a line below it
+        assert_eq!(
+            learned.top[1].iter_ones().collect::<Vec<_>>(),
+            [0, 1, 2, 4, 5]
+        );
+    }
+
+    #[test]
+    fn sample_weights() {
+        assert_eq!(weights_from_sample(&[1, 1, 3, 1]), [0, 3, 0, 1]);
+        assert!(weights_from_sample(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn learn_invalid_width() {
+        adjacency_from_sample(&[0, 1, 2], 2);
+    }
+}
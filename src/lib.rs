@@ -1,2 +1,15 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod board;
 pub mod cell;
+pub mod constraint;
+pub mod graph;
+pub mod grid;
+pub mod learn;
+pub mod puzzles;
+pub mod render;
 pub mod solver;
+pub mod testing;
+pub mod tiles;
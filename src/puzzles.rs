@@ -0,0 +1,151 @@
+use alloc::vec::Vec;
+use bitvec::{array::BitArray, view::BitViewSized};
+use core::fmt::Debug;
+
+use crate::cell::{Cell, CellState};
+
+/// The geometry of a sudoku whose boxes are `BOX_WIDTH` cells wide and
+/// `BOX_HEIGHT` cells tall. The board is `BOX_WIDTH * BOX_HEIGHT` cells along
+/// each side, with as many states per cell
+/// ```
+/// use wave_function_collapse::{cell::Cell, puzzles::Sudoku, solver::SolverBuilder};
+///
+/// // A 6x6 sudoku made of 3x2 boxes
+/// type Puzzle = Sudoku<3, 2>;
+///
+/// let mut solver = SolverBuilder::<u8, { Puzzle::SIDE }, { Puzzle::CELLS }>::new(
+///     Puzzle::neighbors,
+///     Puzzle::reducer,
+/// )
+/// .build().unwrap();
+/// solver.solve().unwrap();
+/// ```
+pub struct Sudoku<const BOX_WIDTH: usize, const BOX_HEIGHT: usize>;
+
+impl<const BOX_WIDTH: usize, const BOX_HEIGHT: usize> Sudoku<BOX_WIDTH, BOX_HEIGHT> {
+    /// The number of cells along each side of the board, which is also the
+    /// number of states each cell can be in
+    pub const SIDE: usize = BOX_WIDTH * BOX_HEIGHT;
+
+    /// The number of cells on the board
+    pub const CELLS: usize = Self::SIDE * Self::SIDE;
+
+    /// Returns every other cell sharing a row, column, or box with the ith
+    pub fn neighbors(i: usize) -> Vec<usize> {
+        let x = i % Self::SIDE;
+        let y = i / Self::SIDE * Self::SIDE;
+        let sect = Self::sector_head(i);
+
+        Self::sector_offsets()
+            .into_iter()
+            .map(move |j| sect + j)
+            .chain((0..Self::SIDE).map(move |j| y + j))
+            .chain((0..Self::SIDE).map(move |j| x + Self::SIDE * j))
+            .filter(move |&j| i != j)
+            .collect()
+    }
+
+    /// Removes every state held by a known neighbor
+    pub fn reducer<A: BitViewSized + Clone + Debug, const N: usize>(
+        neighbors: Vec<(usize, &Cell<A, N>)>,
+        _: usize,
+    ) -> CellState<A> {
+        let mut acc = BitArray::ZERO;
+
+        for (_, cell) in neighbors {
+            acc |= cell.state();
+        }
+
+        acc
+    }
+
+    /// The index of the top left cell of the box containing the ith cell
+    pub fn sector_head(i: usize) -> usize {
+        let sect_y = i / (Self::SIDE * BOX_HEIGHT) * Self::SIDE * BOX_HEIGHT;
+        let sect_x = i % Self::SIDE / BOX_WIDTH * BOX_WIDTH;
+        sect_y + sect_x
+    }
+
+    /// The index of the top left cell of every box
+    pub fn sector_heads() -> Vec<usize> {
+        (0..BOX_WIDTH)
+            .flat_map(|y| (0..BOX_HEIGHT).map(move |x| (y, x)))
+            .map(|(y, x)| y * Self::SIDE * BOX_HEIGHT + x * BOX_WIDTH)
+            .collect()
+    }
+
+    /// The offsets of every cell in a box from the box's top left cell
+    pub fn sector_offsets() -> Vec<usize> {
+        (0..BOX_HEIGHT)
+            .flat_map(|y| (0..BOX_WIDTH).map(move |x| y * Self::SIDE + x))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::solver::{Solver, SolverBuilder};
+
+    type Classic = Sudoku<3, 3>;
+    type Wide = Sudoku<3, 2>;
+
+    #[test]
+    fn classic_sectors() {
+        assert_eq!(Classic::sector_offsets(), [0, 1, 2, 9, 10, 11, 18, 19, 20]);
+        assert_eq!(Classic::sector_heads(), [0, 3, 6, 27, 30, 33, 54, 57, 60]);
+        assert_eq!(Classic::sector_head(40), 30);
+    }
+
+    #[test]
+    fn wide_sectors() {
+        assert_eq!(Wide::sector_offsets(), [0, 1, 2, 6, 7, 8]);
+        assert_eq!(Wide::sector_heads(), [0, 3, 12, 15, 24, 27]);
+        assert_eq!(Wide::sector_head(23), 15);
+    }
+
+    #[test]
+    fn neighbor_counts() {
+        let classic = Classic::neighbors(40).into_iter().collect::<HashSet<_>>();
+        assert_eq!(classic.len(), 20);
+        assert!(!classic.contains(&40));
+
+        let wide = Wide::neighbors(0).into_iter().collect::<HashSet<_>>();
+        assert_eq!(wide.len(), 12);
+        assert!(!wide.contains(&0));
+    }
+
+    #[test]
+    /// Every row, column, and box of a solved board holds each state once
+    fn solve_wide() {
+        let mut solver: Solver<u8, { Wide::SIDE }, { Wide::CELLS }> =
+            SolverBuilder::new(Wide::neighbors, Wide::reducer)
+                .seed(0)
+                .build()
+                .unwrap();
+        solver.solve().unwrap();
+
+        let values = solver
+            .state()
+            .iter()
+            .map(|cell| cell.value().unwrap())
+            .collect::<Vec<usize>>();
+        let groups = (0..Wide::SIDE)
+            .map(|y| (0..Wide::SIDE).map(|x| y * Wide::SIDE + x).collect())
+            .chain((0..Wide::SIDE).map(|x| (0..Wide::SIDE).map(|y| y * Wide::SIDE + x).collect()))
+            .chain(Wide::sector_heads().into_iter().map(|head| {
+                Wide::sector_offsets()
+                    .into_iter()
+                    .map(|j| head + j)
+                    .collect()
+            }))
+            .collect::<Vec<Vec<usize>>>();
+
+        for group in groups {
+            let seen = group.iter().map(|&i| values[i]).collect::<HashSet<_>>();
+            assert_eq!(seen.len(), Wide::SIDE, "Group {:?} repeats a value", group);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use alloc::vec::Vec;
+use bitvec::view::BitViewSized;
+use core::fmt::Debug;
+
+use crate::{cell::Cell, solver::SolverState};
+
+/// An RGB color
+pub type Color = (u8, u8, u8);
+
+/// The color of cells which have no color in the palette
+pub const NEUTRAL: Color = (128, 128, 128);
+
+/// Renders a board to an RGBA buffer with one pixel per cell and `width`
+/// pixels per row, suitable for `image::RgbaImage::from_raw`.
+///
+/// Known cells take the palette color of their value, unknown cells take the
+/// average color of every state they could still be in. Cells without a
+/// matching palette color are rendered as `NEUTRAL`.
+///
+/// Panics if `S` is not a multiple of `width`
+pub fn render_to_rgba<A: BitViewSized + Copy + Debug, const N: usize, const S: usize>(
+    state: &SolverState<A, N, S>,
+    width: usize,
+    palette: &[Color],
+) -> Vec<u8> {
+    render_to_rgba_with_void(state, width, palette, |_| false)
+}
+
+/// Renders a board like `render_to_rgba`, leaving the pixel of each cell for
+/// which `is_void` returns true fully transparent, such as with
+/// `Solver::is_void`
+pub fn render_to_rgba_with_void<A: BitViewSized + Copy + Debug, const N: usize, const S: usize>(
+    state: &SolverState<A, N, S>,
+    width: usize,
+    palette: &[Color],
+    is_void: impl Fn(usize) -> bool,
+) -> Vec<u8> {
+    assert!(
+        width > 0 && S.is_multiple_of(width),
+        "A board of {} cells cannot be {} cells wide",
+        S,
+        width
+    );
+
+    state
+        .iter()
+        .enumerate()
+        .flat_map(|(i, cell)| {
+            if is_void(i) {
+                return [0; 4];
+            }
+
+            let (r, g, b) = color(cell, palette);
+            [r, g, b, u8::MAX]
+        })
+        .collect()
+}
+
+/// The color of a single cell
+fn color<A: BitViewSized + Copy + Debug, const N: usize>(
+    cell: &Cell<A, N>,
+    palette: &[Color],
+) -> Color {
+    if let Some(n) = cell.value() {
+        return palette.get(n).copied().unwrap_or(NEUTRAL);
+    }
+
+    let colors = cell
+        .state()
+        .iter_ones()
+        .filter_map(|n| palette.get(n))
+        .collect::<Vec<&Color>>();
+
+    if colors.is_empty() {
+        return NEUTRAL;
+    }
+
+    let len = colors.len();
+    let sum = colors
+        .into_iter()
+        .fold((0, 0, 0), |(r, g, b), &(cr, cg, cb)| {
+            (r + cr as usize, g + cg as usize, b + cb as usize)
+        });
+
+    (
+        (sum.0 / len) as u8,
+        (sum.1 / len) as u8,
+        (sum.2 / len) as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::array::BitArray;
+
+    use super::*;
+
+    const STATES: usize = 3;
+    type TestCell = Cell<u8, STATES>;
+
+    const PALETTE: [Color; STATES] = [(255, 0, 0), (0, 255, 0), (0, 0, 255)];
+
+    #[test]
+    fn render_known_cells() {
+        let state = [TestCell::collapsed(0), TestCell::reduced(2)];
+        let actual = render_to_rgba(&state, 2, &PALETTE);
+        assert_eq!(actual, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    /// Superpositions blend the colors of their possible states
+    fn render_unknown_cells() {
+        let state = [TestCell::default(), TestCell::unknown(BitArray::new(0b011))];
+        let actual = render_to_rgba(&state, 1, &PALETTE);
+        assert_eq!(actual, vec![85, 85, 85, 255, 127, 127, 0, 255]);
+    }
+
+    #[test]
+    fn render_missing_colors() {
+        let state = [TestCell::collapsed(2), TestCell::unknown(BitArray::ZERO)];
+        let actual = render_to_rgba(&state, 2, &PALETTE[..2]);
+        let (r, g, b) = NEUTRAL;
+        assert_eq!(actual, vec![r, g, b, 255, r, g, b, 255]);
+    }
+
+    #[test]
+    fn render_void_cells() {
+        let state = [TestCell::collapsed(1), TestCell::default()];
+        let actual = render_to_rgba_with_void(&state, 2, &PALETTE, |i| i == 1);
+        assert_eq!(actual, vec![0, 255, 0, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn render_invalid_width() {
+        render_to_rgba(&[TestCell::default(); 3], 2, &PALETTE);
+    }
+}
@@ -1,24 +1,95 @@
+use alloc::{boxed::Box, collections::VecDeque, vec, vec::Vec};
 use bitvec::{array::BitArray, order::Lsb0, view::BitViewSized};
-use rand::{
-    prelude::{thread_rng, SliceRandom, StdRng},
-    SeedableRng,
+use core::{
+    fmt::{self, Debug},
+    mem,
+    ops::Range,
 };
-use std::fmt::Debug;
+use rand::{distributions::WeightedError, prelude::StdRng, Rng, SeedableRng};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
-use crate::cell::{Cell, Weights};
+use crate::{
+    cell::{Cell, CellState, Reduction, Weights},
+    constraint::{compose, Constraint},
+    grid::{
+        grid_neighbors, torus_neighbors, DirectedNeighbors, Direction, DirectionalWeights,
+        NeighborsByDir,
+    },
+};
 
 /// Represents the state of the solver at a given time
 pub type SolverState<A, const N: usize, const S: usize> = [Cell<A, N>; S];
 
-/// A function which returns cells adjacent to a given index
+/// A function which returns cells adjacent to a given index. Adjacency is
+/// read one way, a cell is only reduced by the cells it lists, so when
+/// neighbors are asymmetric a cell which is known before a cell it lists is
+/// never checked against it. List both cells of each pair for a rule to hold
+/// in both directions, `Solver::verify` reports cells it was broken for
 pub type Neighbors = fn(usize) -> Vec<usize>;
 
-/// A function which returns a BitArray where each 1 represents a state
-/// that the current tile cannot be in
+/// A function which returns the states the current tile cannot be in, either
+/// as a `Reduction`, which may force the tile to a single state, or as a
+/// BitArray where each 1 represents a denied state.
+/// Any function or closure with the same signature can be boxed into a reducer
 pub type StateReducer<A, const N: usize> =
-    fn(Vec<(usize, &Cell<A, N>)>, usize) -> BitArray<A, Lsb0>;
+    Box<dyn Fn(Vec<(usize, &Cell<A, N>)>, usize) -> Reduction<A> + Send + Sync>;
+
+/// A function called with the index and value of each observed cell, along
+/// with the state of the board after the observation
+pub type OnCollapse<A, const N: usize, const S: usize> = fn(usize, usize, &SolverState<A, N, S>);
+
+/// A function given the index and value of a proposed observation along with
+/// the state of the board before it, which returns whether it may be made
+pub type ValidateObservation<A, const N: usize, const S: usize> =
+    fn(usize, usize, &SolverState<A, N, S>) -> bool;
+
+/// Weights which may change as the board fills in, such as to make a tile
+/// rarer each time it's placed. Closures with the same signature as `weight`
+/// are dynamic weights too, see `SolverBuilder::dynamic_weights`
+pub trait DynamicWeights<A: BitViewSized + Copy + Debug, const N: usize, const S: usize>:
+    Send + Sync
+{
+    /// The weight of observing `state` given the board before the
+    /// observation
+    fn weight(&mut self, state: usize, board: &SolverState<A, N, S>) -> f64;
+}
+
+impl<A, const N: usize, const S: usize, F> DynamicWeights<A, N, S> for F
+where
+    A: BitViewSized + Copy + Debug,
+    F: FnMut(usize, &SolverState<A, N, S>) -> f64 + Send + Sync,
+{
+    fn weight(&mut self, state: usize, board: &SolverState<A, N, S>) -> f64 {
+        self(state, board)
+    }
+}
+
+/// Each neighbor of a cell along with the states it removed from the cell,
+/// see `Solver::explain`
+pub type Explanation<A> = Vec<(usize, CellState<A>)>;
+
+/// Bounds which are only required of a cell's storage when the `parallel`
+/// feature is enabled and cells are shared between threads
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Send + Sync {}
+
+#[cfg(feature = "parallel")]
+impl<T: Send + Sync> MaybeSync for T {}
+
+/// Bounds which are only required of a cell's storage when the `parallel`
+/// feature is enabled and cells are shared between threads
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> MaybeSync for T {}
 
 /// Solves a constraint problem using wave function collapse and backtracking
+///
+/// Solving requires `&mut Solver`, but nothing is mutated through a shared
+/// reference, so a solved board can be read from many threads at once through
+/// `&Solver` whenever the storage and rng are `Sync`, as `StdRng` is
 /// ```
 /// use bitvec::{array::BitArray, order::Lsb0};
 /// use wave_function_collapse::{cell::Cell, solver::SolverBuilder};
@@ -51,20 +122,23 @@ pub type StateReducer<A, const N: usize> =
 /// fn main() -> Result<(), Box<dyn std::error::Error>> {
 ///     let solver = SolverBuilder::new(neighbors, reducer)
 ///         .state([MyCell::default(); BOARD_SIZE])
-///         .build();
+///         .build()?;
 ///     Ok(())
 /// }
 /// ```
-pub struct Solver<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> {
+pub struct Solver<A: BitViewSized + Copy + Debug, const N: usize, const S: usize, R: Rng = StdRng> {
     /// Current state of the board
     state: SolverState<A, N, S>,
 
     /// A stack of the historic board states
-    history: Vec<SolverState<A, N, S>>,
+    history: VecDeque<Frame<A, N, S>>,
+
+    /// The most historic board states to keep before the oldest are dropped
+    max_history: Option<usize>,
 
     /// A function which returns a list of adjacent cells used to filter input
     /// to `reducer`
-    neighbors: Neighbors,
+    neighbors: NeighborFn,
 
     /// A function which returns a `BitArray` where each 1 represents a state that the current ith
     /// cannot be in
@@ -73,264 +147,4514 @@ pub struct Solver<A: BitViewSized + Copy + Debug, const N: usize, const S: usize
     /// A function which returns the weight associated with a given state
     weights: Weights,
 
+    /// Scales `weights` by the directions of each cell's known neighbors
+    directional_weights: Option<DirectionalWeights>,
+
+    /// Replaces `weights` with weights which depend on the board
+    dynamic_weights: Option<Box<dyn DynamicWeights<A, N, S>>>,
+
     /// Random noise for selecting and solving cells
-    rng: StdRng,
+    rng: R,
+
+    /// Called after each observation, but not for cells reduced by
+    /// propagation
+    on_collapse: Option<OnCollapse<A, N, S>>,
+
+    /// Vetoes observations which break rules the reducer can't express
+    validate_observation: Option<ValidateObservation<A, N, S>>,
+
+    /// Called with each step the solver takes, see `SolverBuilder::trace`
+    trace: Option<fn(SolverEvent)>,
+
+    /// The longest a single call to `solve` may run for
+    #[cfg(feature = "std")]
+    timeout: Option<Duration>,
+
+    /// Sharpens or flattens the weights of each observation
+    #[cfg(feature = "std")]
+    temperature: Option<f64>,
+
+    /// How the solver recovers from a contradiction
+    recovery: Recovery,
+
+    /// Which cell is observed first on an empty board
+    start: StartStrategy,
+
+    /// Whether to observe the cells with the lowest or highest entropy
+    heuristic: EntropyHeuristic,
+
+    /// Whether to check the solver's invariants after each observation
+    debug: bool,
+
+    /// Whether ties between cells are broken by index rather than the rng
+    deterministic: bool,
+
+    /// Whether the known cells are propagated without backtracking before
+    /// the first observation, see `SolverBuilder::deduce_first`
+    deduce_first: bool,
+
+    /// The state restored when restarting
+    initial: SolverState<A, N, S>,
+
+    /// The number of observations made before the most recent restart
+    restart_mark: usize,
+
+    /// The number of restarts or clears made since the current solve began
+    recoveries: usize,
+
+    /// Counters describing the work done by the solver
+    stats: SolverStats,
+
+    /// Cells which are never observed, reduced, or panned, see `freeze`
+    frozen: [bool; S],
+
+    /// Cells which are not part of the board, see `SolverBuilder::void`
+    void: [bool; S],
+
+    /// The width and height of the board, see `SolverBuilder::dimensions`
+    dimensions: Option<(usize, usize)>,
+
+    /// The index, state, and weight multiplier of each preference, see
+    /// `prefer`
+    preferences: Vec<(usize, usize, f64)>,
+
+    /// The states each neighbor removed from each cell at its last reduction,
+    /// only recorded when enabled with `SolverBuilder::explain`
+    explanations: Option<Vec<Explanation<A>>>,
+
+    /// Which cells the reducer has removed states from since the last reset,
+    /// only recorded when enabled with `SolverBuilder::track_inert`
+    touched: Option<[bool; S]>,
+
+    /// Counts observations and edits to the board, so each has its own time
+    clock: usize,
+
+    /// For each cell, the latest time of an observation or edit its possible
+    /// states were derived from, only tracked for `Recovery::Backjump`
+    reasons: Option<[usize; S]>,
 }
 
-impl<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> Solver<A, N, S> {
+impl<
+        A: BitViewSized + Copy + Debug + MaybeSync,
+        const N: usize,
+        const S: usize,
+        R: Rng + MaybeSync,
+    > Solver<A, N, S, R>
+{
     /// Returns the current state of the solver
     pub fn state(&self) -> &SolverState<A, N, S> {
         &self.state
     }
 
-    /// Fills in every unsolved cell
-    pub fn solve(&mut self) {
-        let mut to_collapse = self.reduced();
+    /// Counters describing the work done by the solver across every solve
+    pub fn stats(&self) -> &SolverStats {
+        &self.stats
+    }
+
+    /// The number of states each cell could be in, in index order
+    pub fn entropy_map(&self) -> Vec<usize> {
+        self.state.iter().map(Cell::entropy).collect()
+    }
+
+    /// The Shannon entropy of each cell under the solver's weights, in index
+    /// order
+    #[cfg(feature = "std")]
+    pub fn entropy_map_f64(&self) -> Vec<f64> {
+        self.state
+            .iter()
+            .map(|cell| cell.shannon_entropy(self.weights))
+            .collect()
+    }
 
-        self.history.push(self.state.clone());
-        self.propagate(to_collapse);
+    /// The cells adjacent to the ith, as returned by the solver's neighbors
+    /// function
+    pub fn neighbors_of(&self, i: usize) -> Vec<usize> {
+        self.neighbors.indices(i)
+    }
+
+    /// The number of cells which are still unknown, not counting frozen cells
+    pub fn unsolved_count(&self) -> usize {
+        (0..S)
+            .filter(|&i| self.state[i].is_unknown() && !self.frozen[i])
+            .count()
+    }
 
-        while let Some(i) = self.lowest_entropy() {
-            to_collapse = self.observe(i);
-            self.propagate(to_collapse);
+    /// The fraction of cells which are known, from 0 to 1. An empty board is
+    /// considered fully solved
+    pub fn progress(&self) -> f64 {
+        if S == 0 {
+            return 1.0;
         }
+
+        (S - self.unsolved_count()) as f64 / S as f64
     }
 
-    /// Pans the solver, shifting the entire state by the distance in `Pan`
-    pub fn pan(&mut self, pan: Pan, row_len: usize) {
-        match pan {
-            Pan::Left(distance) => {
-                self.pan_direction((0..S).rev(), &|i| i % row_len > distance, &|i| i - distance)
-            }
-            Pan::Right(distance) => {
-                self.pan_direction(0..S, &|i| row_len - i % row_len > distance, &|i| {
-                    i + distance
-                })
+    /// The fraction of the initial state's entropy which has been removed,
+    /// from 0 to 1, measured as the sum of the log of each cell's possible
+    /// states. Unlike `progress` this also credits cells which were only
+    /// partly reduced, so it advances more smoothly when one observation
+    /// cascades through the board
+    #[cfg(feature = "std")]
+    pub fn estimated_progress(&self) -> f64 {
+        let entropy = |state: &SolverState<A, N, S>| {
+            (0..S)
+                .filter(|&i| !self.frozen[i])
+                .map(|i| (state[i].entropy().max(1) as f64).ln())
+                .sum::<f64>()
+        };
+        let initial = entropy(&self.initial);
+
+        if initial == 0.0 {
+            return 1.0;
+        }
+
+        (1.0 - entropy(&self.state) / initial).max(0.0)
+    }
+
+    /// Each known neighbor of the ith cell along with the states it removed
+    /// from the cell at its last reduction, which may be from a branch which
+    /// was since backtracked. Neighbors are attributed by calling the reducer
+    /// with each alone, so states only denied by several neighbors together
+    /// are left out. Empty unless built with `SolverBuilder::explain`
+    pub fn explain(&self, i: usize) -> Explanation<A> {
+        self.explanations
+            .as_ref()
+            .map_or_else(Vec::new, |explanations| explanations[i].clone())
+    }
+
+    /// The cells which the reducer hasn't removed a single state from since
+    /// the solver was built or reset, leaving out void cells and cells known
+    /// from the start. A cell which stays inert over a solve was only ever
+    /// observed, which can point to a gap in the reducer, though on an empty
+    /// board the first observed cell is always inert. Empty unless built with
+    /// `SolverBuilder::track_inert`
+    pub fn inert_cells(&self) -> Vec<usize> {
+        let Some(touched) = &self.touched else {
+            return vec![];
+        };
+
+        (0..S)
+            .filter(|&i| !touched[i] && !self.void[i] && self.initial[i].is_unknown())
+            .collect()
+    }
+
+    /// For each possible state of the ith cell, the number of pairs of a
+    /// neighbor and one of that neighbor's possible states which allow it.
+    /// States the cell can't be in have no support. The counts are summed over
+    /// every neighbor, so a state one neighbor gives no support may still
+    /// have a nonzero count. Each neighbor state is checked by calling the
+    /// reducer with that neighbor alone
+    pub fn supports(&self, i: usize) -> [usize; N] {
+        let mut supports = [0; N];
+        let possible = self.state[i].state();
+
+        for j in self.neighbors.indices(i) {
+            for m in self.state[j].state().iter_ones().take_while(|&m| m < N) {
+                let denied = (self.reducer)(vec![(j, &Cell::reduced(m))], i).into_deny();
+
+                for n in possible.iter_ones().take_while(|&n| n < N) {
+                    if !denied[n] {
+                        supports[n] += 1;
+                    }
+                }
             }
-            Pan::Up(distance) => {
-                self.pan_direction((0..S).rev(), &|i| i / row_len > distance, &|i| {
-                    (i / row_len - distance) * row_len + i % row_len
-                })
+        }
+
+        supports
+    }
+
+    /// The number of historic states the solver can backtrack through
+    pub fn history_depth(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Runs the reducer against the cells which are already known and returns
+    /// the indices of any cells left without a possible state
+    pub fn check_initial(&self) -> Result<(), Vec<usize>> {
+        self.conflicts(|cell, denied| (cell.state() & !denied).not_any())
+    }
+
+    /// Checks each known cell against the reducer, given its known neighbors,
+    /// and returns the indices of any whose value the reducer would deny. On a
+    /// solved board this confirms the solution follows the reducer's rules
+    /// without a separate validator, and catches reducers which propagate
+    /// inconsistently
+    pub fn verify(&self) -> Result<(), Vec<usize>> {
+        self.conflicts(|cell, denied| cell.value().is_some_and(|n| denied[n]))
+    }
+
+    /// Returns the indices of the cells for which `conflicted` holds, given the
+    /// cell and the states the reducer denies it from its known neighbors
+    fn conflicts(
+        &self,
+        conflicted: impl Fn(&Cell<A, N>, BitArray<A, Lsb0>) -> bool,
+    ) -> Result<(), Vec<usize>> {
+        let conflicts = (0..S)
+            .filter(|&i| !self.void[i])
+            .filter(|&i| {
+                let neighbors = self.known_neighbors(i);
+                let denied = if neighbors.is_empty() {
+                    BitArray::ZERO
+                } else {
+                    (self.reducer)(neighbors, i).into_deny()
+                };
+
+                conflicted(&self.state[i], denied)
+            })
+            .collect::<Vec<usize>>();
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Removes `value` from the possible states of the ith cell without
+    /// collapsing it, propagating if the cell is left with a single state.
+    /// Fails without backtracking if that leaves a cell without any possible
+    /// states, in which case the board is restored to how it was before, or
+    /// if `value` isn't one of the states
+    pub fn forbid(&mut self, i: usize, value: usize) -> Result<(), SolverError> {
+        if value >= N {
+            return Err(BuildError::Given(i, value).into());
+        }
+
+        if self.state[i].value() == Some(value) {
+            return Err(SolverError::Contradiction(i));
+        }
+
+        let before = self.state;
+        let mut reduction = BitArray::ZERO;
+        reduction.set(value, true);
+        self.edit();
+
+        self.state[i] = self.state[i]
+            .reduce(reduction)
+            .cell()
+            .ok_or(SolverError::Contradiction(i))?;
+
+        if self.state[i].is_reduced() {
+            if let Err(e) = self.propagate_without_backtracking(vec![i]) {
+                self.state = before;
+                return Err(e);
             }
-            Pan::Down(distance) => {
-                self.pan_direction(0..S, &|i| S / row_len - i / row_len > distance, &|i| {
-                    (i / row_len + distance) * row_len + i % row_len
-                })
+        }
+
+        Ok(())
+    }
+
+    /// Sets the ith cell to `value` and propagates it, returning the indices
+    /// of every cell whose variant changed, such as cells which were forced to
+    /// a single state, so only those need redrawing. Fails without
+    /// backtracking if the cell cannot be `value` or the assignment leaves
+    /// another cell without any possible states, though the cells reduced
+    /// before the contradiction are not restored
+    pub fn assign(&mut self, i: usize, value: usize) -> Result<Vec<usize>, SolverError> {
+        if let Some(n) = self.state[i].value() {
+            return if n == value {
+                Ok(vec![])
+            } else {
+                Err(SolverError::Contradiction(i))
+            };
+        }
+
+        let before = self.state;
+        self.edit();
+        self.state[i] = self.state[i]
+            .collapse_to(value)
+            .ok_or(SolverError::Contradiction(i))?;
+        self.propagate_without_backtracking(vec![i])?;
+
+        Ok((0..S)
+            .filter(|&j| mem::discriminant(&before[j]) != mem::discriminant(&self.state[j]))
+            .collect())
+    }
+
+    /// Assigns each `(index, value)` pair in order. If any assignment fails
+    /// the board and its history are restored to how they were before the
+    /// first, and the position of the failed assignment is returned
+    pub fn try_assign_all(
+        &mut self,
+        assignments: &[(usize, usize)],
+    ) -> Result<(), (usize, SolverError)> {
+        let state = self.state;
+        let history = self.history.clone();
+
+        for (k, &(i, value)) in assignments.iter().enumerate() {
+            if let Err(e) = self.assign(i, value) {
+                self.state = state;
+                self.history = history;
+                return Err((k, e));
             }
         }
 
-        self.history = vec![self.state.clone()];
+        Ok(())
     }
 
-    /// Iterates over the board and propagate collapsed cells
-    fn propagate(&mut self, to_collapse: Vec<usize>) {
-        let mut to_collapse = to_collapse;
-        let mut reduced = vec![];
+    /// Fills in every unsolved cell. Solving runs entirely on the calling
+    /// thread, so it is safe to call from single threaded targets like WASM
+    pub fn solve(&mut self) -> Result<(), SolverError> {
+        self.run(usize::MAX).map(|_| ())
+    }
 
-        while !to_collapse.is_empty() {
-            // println!("p: Collapsing: {:?}", to_collapse);
-            for i in 0..S {
-                if !self.state[i].is_unknown() {
-                    continue;
-                }
+    /// Observes at most `n` cells, fully propagating after each, and returns
+    /// how many more cells are known than before, counting cells forced by
+    /// propagation but not observations which were backtracked. This leaves a
+    /// partially solved board, such as the givens of a new puzzle
+    pub fn solve_n(&mut self, n: usize) -> Result<usize, SolverError> {
+        let unsolved = self.unsolved_count();
+        self.run(n)?;
+        Ok(unsolved.saturating_sub(self.unsolved_count()))
+    }
 
-                let neighbors = (self.neighbors)(i)
-                    .iter()
-                    .filter(|&&j| !self.state[j].is_unknown())
-                    .map(|&j| (j, &self.state[j]))
-                    .collect::<Vec<(usize, &Cell<A, N>)>>();
+    /// Observes at most `max_steps` cells so a large board can be solved a
+    /// little at a time, such as once per frame. Each observation is fully
+    /// propagated before returning and the history carries over between
+    /// calls, so a later call may backtrack into an earlier one
+    pub fn solve_budget(&mut self, max_steps: usize) -> SolveStatus {
+        if self.history.is_empty() {
+            self.push_history(Frame::new(self.state));
+        }
 
-                if neighbors.is_empty() {
-                    continue;
-                }
+        match self.search(self.reduced(), max_steps) {
+            Ok(_) if self.unsolved_count() == 0 => SolveStatus::Solved,
+            Ok(_) => SolveStatus::InProgress,
+            Err(e) => SolveStatus::Failed(e),
+        }
+    }
 
-                let reductions = (self.reducer)(neighbors, i);
+    /// Propagates the known cells without observing any, so only states ruled
+    /// out by the reducer are removed. Returns whether that alone solved the
+    /// board, if it stalls with unknown cells the caller can decide whether to
+    /// start guessing with `solve`. Fails on the first contradiction rather
+    /// than backtracking
+    pub fn propagate_to_fixpoint(&mut self) -> Result<bool, SolverError> {
+        let to_collapse = self.reduced();
+        self.propagate_without_backtracking(to_collapse)?;
+        Ok(self.unsolved_count() == 0)
+    }
 
-                if reductions.not_any() {
-                    continue;
-                }
+    /// Restores the state the solver was built with, or last panned to, and
+    /// forgets the history
+    pub fn reset(&mut self) {
+        self.state = self.initial;
+        self.history.clear();
+        self.recoveries = 0;
 
-                // print!("p: Reducing {i} to ");
-                match self.state[i].reduce(reductions) {
-                    Some(cell) => self.state[i] = cell,
-                    None => {
-                        // println!(" no possibilities");
-                        let to_collapse = self.backtrack();
-                        self.propagate(to_collapse);
-                        return;
-                    }
-                }
-                // println!("{:?}", self.state[i]);
+        if let Some(explanations) = &mut self.explanations {
+            explanations.iter_mut().for_each(Vec::clear);
+        }
 
-                if self.state[i].is_reduced() {
-                    reduced.push(i);
-                }
+        if let Some(touched) = &mut self.touched {
+            *touched = [false; S];
+        }
+
+        if let Some(reasons) = &mut self.reasons {
+            *reasons = [0; S];
+        }
+    }
+
+    /// Changes the given at the ith cell to `value`, or removes it, without
+    /// rebuilding the solver. Known cells which weren't given are kept unless
+    /// their known neighbors now rule them out, in which case they're cleared
+    /// back to unknown, then the board is propagated again. If the kept cells
+    /// leave the board without a solution every cell which wasn't given is
+    /// cleared. Every historic state held the old given so the whole history
+    /// is discarded, backtracking can't undo the edit or anything before it.
+    /// Returns the cells which were cleared, and fails if `value` isn't one of
+    /// the states or the givens contradict each other, in which case the
+    /// solver is left as it was
+    ///
+    /// Panics if `i` is outside the board
+    pub fn edit_given(
+        &mut self,
+        i: usize,
+        value: Option<usize>,
+    ) -> Result<Vec<usize>, SolverError> {
+        if let Some(n) = value.filter(|&n| n >= N) {
+            return Err(BuildError::Given(i, n).into());
+        }
+
+        let before = self.state;
+        let initial = self.initial;
+        let history = mem::take(&mut self.history);
+        let cell = value.map_or_else(Cell::default, Cell::reduced);
+        self.initial[i] = cell;
+        self.state[i] = cell;
+
+        let mut cleared = vec![];
+
+        for j in (0..S).filter(|&j| j != i && self.initial[j].is_unknown() && !self.void[j]) {
+            if self.state[j].is_unknown() {
+                self.state[j] = self.initial[j];
+                continue;
             }
 
-            for i in to_collapse {
-                self.state[i] = self.state[i].collapse();
+            let neighbors = self.known_neighbors(j);
+            let denied = !neighbors.is_empty()
+                && self.state[j]
+                    .value()
+                    .is_some_and(|n| (self.reducer)(neighbors, j).into_deny()[n]);
+
+            if denied {
+                cleared.push(j);
             }
+        }
 
-            to_collapse = reduced;
-            reduced = vec![];
+        for &j in &cleared {
+            self.state[j] = self.initial[j];
+        }
+
+        // Propagating from the edited cell runs a wave even when no cell is
+        // reduced, so a removed given is reduced against its neighbors
+        if self
+            .propagate_without_backtracking([vec![i], self.reduced()].concat())
+            .is_ok()
+        {
+            return Ok(cleared);
+        }
+
+        self.state = self.initial;
+        cleared = (0..S)
+            .filter(|&j| j != i && !before[j].is_unknown() && self.state[j].is_unknown())
+            .collect();
+
+        if let Err(error) = self.propagate_without_backtracking([vec![i], self.reduced()].concat())
+        {
+            self.state = before;
+            self.initial = initial;
+            self.history = history;
+            return Err(error);
         }
+
+        Ok(cleared)
     }
 
-    /// Randomly selects once cell with the lowest entropy
-    fn lowest_entropy(&mut self) -> Option<usize> {
-        let mut cells = self
-            .state
-            .iter()
-            .enumerate()
-            .filter(|(_, c)| c.is_unknown())
-            // .inspect(|(_, c)| assert!(c.is_unknown()))
-            .collect::<Vec<(usize, &Cell<A, N>)>>();
+    /// Solves only the cells at `indices`, such as after editing part of a
+    /// large map. Every other cell is left exactly as it is, known cells
+    /// constrain the region and unknown cells stay unknown, even when the
+    /// solve backtracks or restarts. The history is left as it was, and if
+    /// the region can't be solved the board is too.
+    ///
+    /// Panics if an index is outside the board
+    pub fn solve_region(&mut self, indices: &[usize]) -> Result<(), SolverError> {
+        let before = self.state;
+        let frozen = self.frozen;
+        let initial = self.initial;
+        let history = mem::take(&mut self.history);
 
-        if cells.is_empty() {
-            return None;
+        self.frozen = [true; S];
+        for &i in indices {
+            self.frozen[i] = frozen[i];
         }
+        self.initial = self.state;
 
-        cells.sort_by(|&(_, c1), &(_, c2)| c1.entropy().cmp(&c2.entropy()));
+        let result = self.solve();
 
-        let least_entropy = cells[0].1.entropy();
+        // Propagation collapses reduced cells, including those outside
+        for i in (0..S).filter(|&i| self.frozen[i]) {
+            self.state[i] = before[i];
+        }
 
-        cells
-            .iter()
-            .take_while(|(_, c)| c.entropy() == least_entropy)
-            .map(|&(i, _)| i)
-            .collect::<Vec<usize>>()
-            .choose(&mut self.rng)
-            .map(ToOwned::to_owned)
+        if result.is_err() {
+            self.state = before;
+        }
+
+        self.frozen = frozen;
+        self.initial = initial;
+        self.history = history;
+        result
     }
 
-    /// Tries to solve a cell, if there is no solution it resets the board
-    fn observe(&mut self, i: usize) -> Vec<usize> {
-        // println!("o: Observing {i} {:?}", self.state[i]);
-        match self.state[i].observe(self.weights, &mut self.rng) {
-            Ok(cell) => {
-                self.history.push({
-                    let mut state = self.state.clone();
-                    match state[i].reduce(cell.state()) {
-                        Some(cell) => state[i] = cell,
-                        _ => {}
+    /// Solves the board `n` times from its initial state, each with fresh
+    /// draws from the rng, and keeps the solution with the highest `score`.
+    /// Failed solves are skipped, if every solve fails the last error is
+    /// returned
+    pub fn solve_best_of(
+        &mut self,
+        n: usize,
+        score: fn(&SolverState<A, N, S>) -> f64,
+    ) -> Result<SolverState<A, N, S>, SolverError> {
+        let mut best: Option<(f64, SolverState<A, N, S>)> = None;
+        let mut error = SolverError::Unsolvable;
+
+        for _ in 0..n {
+            self.reset();
+
+            match self.solve() {
+                Ok(()) => {
+                    let value = score(&self.state);
+                    if best.is_none_or(|(best, _)| value > best) {
+                        best = Some((value, self.state));
                     }
-                    // assert!(state[i].is_unknown());
-                    state
-                });
-                self.state[i] = cell;
-                vec![i]
+                }
+                Err(e) => error = e,
             }
-            Err(_) => self.backtrack(),
         }
+
+        let (_, state) = best.ok_or(error)?;
+        self.state = state;
+        self.history.clear();
+        Ok(state)
     }
 
-    fn backtrack(&mut self) -> Vec<usize> {
-        // println!("backtracking!");
-        // println!("{:?}", self.// print_board());
-        match self.history.pop() {
-            Some(state) => {
-                self.state = state;
-                // println!("{:?}", self.// print_board());
-                // println!("{:?}", self.reduced());
-                self.reduced()
-            }
-            None => {
-                // println!("Input State:\n{:?}", self.print_board());
-                // println!("retrying!");
-                self.state = self.state;
-                self.reduced()
-            }
+    /// Lazily enumerates every distinct solution reachable from the current
+    /// state by backtracking, so `.take(2)` cheaply checks for uniqueness.
+    /// The history is discarded first, so only solutions which keep every
+    /// cell already known are found, call `reset` beforehand to enumerate the
+    /// solutions of the initial state instead. `Recovery::Restart` is ignored
+    /// while enumerating, and capping the history with `max_history` may
+    /// cause solutions to be missed
+    pub fn solutions(&mut self) -> Solutions<'_, A, N, S, R> {
+        let recovery = self.recovery;
+        self.recovery = Recovery::Backtrack;
+        self.history.clear();
+
+        Solutions {
+            solver: self,
+            recovery,
+            started: false,
+            done: false,
         }
     }
 
-    fn reduced(&self) -> Vec<usize> {
-        self.state
-            .iter()
-            .enumerate()
-            .filter(|(_, c)| c.is_reduced())
-            .map(|(i, _)| i)
-            .collect::<Vec<usize>>()
+    /// Propagates the known cells then observes up to `limit` cells
+    fn run(&mut self, limit: usize) -> Result<usize, SolverError> {
+        self.recoveries = 0;
+
+        if self.deduce_first {
+            self.propagate_without_backtracking(self.reduced())?;
+        }
+
+        let to_collapse = self.reduced();
+        self.push_history(Frame::new(self.state));
+        self.search(to_collapse, limit)
     }
 
-    fn pan_direction<'a, I: Iterator<Item = usize>>(
-        &mut self,
-        iter: I,
-        predicate: &'a dyn Fn(usize) -> bool,
-        accessor: &'a dyn Fn(usize) -> usize,
-    ) {
-        for i in iter {
-            self.state[i] = if predicate(i) {
-                self.state[accessor(i)]
-            } else {
-                Cell::<A, N>::default()
+    /// Propagates `to_collapse` then observes up to `limit` cells
+    fn search(&mut self, to_collapse: Vec<usize>, limit: usize) -> Result<usize, SolverError> {
+        #[cfg(feature = "std")]
+        let start_time = Instant::now();
+        let mut to_collapse = to_collapse;
+        let mut observations = 0;
+
+        if let Some(i) = self.state.iter().position(Cell::is_contradiction) {
+            log::trace!("Cell {} has no possible states", i);
+            return Err(SolverError::Unsolvable);
+        }
+
+        self.propagate(to_collapse)?;
+        self.debug_check();
+
+        while observations < limit {
+            let Some(i) = self.next_cell() else {
+                break;
             };
+
+            #[cfg(feature = "std")]
+            if let Some(timeout) = self.timeout {
+                if start_time.elapsed() >= timeout {
+                    return Err(SolverError::Timeout);
+                }
+            }
+
+            to_collapse = self.observe(i)?;
+            self.propagate(to_collapse)?;
+            self.debug_check();
+            observations += 1;
         }
+
+        Ok(observations)
     }
-}
 
-/// The direction and distance to pan
-pub enum Pan {
-    Left(usize),
-    Right(usize),
-    Up(usize),
-    Down(usize),
-}
+    /// The width and height of the board, if built with
+    /// `SolverBuilder::dimensions` or `SolverBuilder::grid2d`
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        self.dimensions
+    }
 
-pub struct SolverBuilder<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> {
-    seed: Option<u64>,
-    state: Option<SolverState<A, N, S>>,
-    neighbors: Neighbors,
-    reducer: StateReducer<A, N>,
-    weights: Option<Weights>,
-}
+    /// Pans the solver like `pan`, using the width of the board it was built
+    /// with.
+    ///
+    /// Panics if the solver was built without dimensions
+    pub fn scroll(&mut self, pan: Pan) {
+        let (width, _) = self
+            .dimensions
+            .expect("Scrolling requires a solver built with dimensions");
+        self.pan(pan, width);
+    }
 
-fn uniform(_: &usize) -> usize {
-    1
-}
+    /// Pans the solver, shifting the entire state by the distance in `Pan`
+    pub fn pan(&mut self, pan: Pan, row_len: usize) {
+        let (dx, dy) = match pan {
+            Pan::Left(distance) => (-(distance as isize), 0),
+            Pan::Right(distance) => (distance as isize, 0),
+            Pan::Up(distance) => (0, -(distance as isize)),
+            Pan::Down(distance) => (0, distance as isize),
+        };
 
-impl<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> SolverBuilder<A, N, S> {
-    pub fn new(neighbors: Neighbors, reducer: StateReducer<A, N>) -> Self {
-        Self {
-            seed: None,
-            state: None,
-            neighbors,
-            reducer,
-            weights: None,
+        self.pan_xy(dx, dy, row_len);
+    }
+
+    /// Pans the solver right by `dx` and down by `dy` cells at once, so a
+    /// diagonal pan only forgets the history a single time. Negative values
+    /// pan left and up, and cells panned in from outside the board are unknown
+    pub fn pan_xy(&mut self, dx: isize, dy: isize, row_len: usize) {
+        let panned = self.state;
+        let rows = (S / row_len) as isize;
+
+        for i in (0..S).filter(|&i| !self.frozen[i]) {
+            let x = (i % row_len) as isize + dx;
+            let y = (i / row_len) as isize + dy;
+            let source = ((0..row_len as isize).contains(&x) && (0..rows).contains(&y))
+                .then(|| y as usize * row_len + x as usize)
+                .filter(|&j| !self.frozen[j]);
+
+            self.state[i] = source.map_or_else(Cell::default, |j| panned[j]);
         }
+
+        self.rebase();
     }
 
-    pub fn seed(mut self, seed: u64) -> Self {
-        self.seed = Some(seed);
-        self
+    /// Multiplies the weight of `value` by `weight` whenever the ith cell is
+    /// observed, replacing any earlier preference for the same state. Unlike
+    /// `assign` nothing is collapsed and no other state is ruled out, so the
+    /// preference is ignored if propagation removes the state. The weight must
+    /// not be negative
+    pub fn prefer(&mut self, i: usize, value: usize, weight: f64) {
+        self.preferences.retain(|&(j, n, _)| (j, n) != (i, value));
+        self.preferences.push((i, value, weight));
     }
 
-    pub fn state(mut self, state: SolverState<A, N, S>) -> Self {
-        self.state = Some(state);
-        self
+    /// Whether the ith cell was carved out of the board with
+    /// `SolverBuilder::void`
+    pub fn is_void(&self, i: usize) -> bool {
+        self.void[i]
     }
 
-    pub fn weights(mut self, weights: Weights) -> Self {
-        self.weights = Some(weights);
-        self
+    /// Keeps the cells at `indices` as they are, such as hand authored content
+    /// or a HUD. Frozen cells are never observed, reduced, or moved by
+    /// panning, and cells panned in from under them are unknown. Known frozen
+    /// cells still constrain their neighbors, and backtracking or resetting
+    /// leaves frozen cells as they were when frozen
+    pub fn freeze(&mut self, indices: &[usize]) {
+        for &i in indices {
+            self.frozen[i] = true;
+            self.initial[i] = self.state[i];
+
+            for frame in &mut self.history {
+                frame.state[i] = self.state[i];
+            }
+        }
     }
 
-    pub fn build(self) -> Solver<A, N, S> {
-        Solver {
-            state: match self.state {
-                Some(state) => state,
-                None => [Cell::default(); S],
-            },
-            history: vec![],
-            neighbors: self.neighbors,
-            reducer: self.reducer,
-            weights: match self.weights {
-                Some(weights) => weights,
-                None => uniform,
-            },
-            rng: match self.seed {
-                Some(seed) => StdRng::seed_from_u64(seed),
-                None => StdRng::from_rng(thread_rng()).unwrap(),
-            },
+    /// Makes the current state the initial state, forgetting the history
+    fn rebase(&mut self) {
+        self.history.clear();
+        self.push_history(Frame::new(self.state));
+        self.initial = self.state;
+
+        if let Some(reasons) = &mut self.reasons {
+            *reasons = [0; S];
+        }
+    }
+
+    /// Iterates over the board and propagate collapsed cells
+    fn propagate(&mut self, to_collapse: Vec<usize>) -> Result<(), SolverError> {
+        let mut to_collapse = to_collapse;
+
+        while !to_collapse.is_empty() {
+            log::trace!("Collapsing {:?}", to_collapse);
+            let reduced = match self.reduce_wave() {
+                Ok(reduced) => reduced,
+                Err(i) => {
+                    log::trace!("Cell {} has no possible states left", i);
+                    self.emit(SolverEvent::Contradiction { cell: i });
+                    let to_collapse = self.backtrack(Some(i))?;
+                    return self.propagate(to_collapse);
+                }
+            };
+
+            for i in to_collapse {
+                self.state[i] = self.state[i].collapse();
+            }
+
+            to_collapse = reduced;
         }
+
+        Ok(())
+    }
+
+    /// Propagates collapsed cells like `propagate`, but fails on the first
+    /// contradiction rather than backtracking
+    fn propagate_without_backtracking(
+        &mut self,
+        to_collapse: Vec<usize>,
+    ) -> Result<(), SolverError> {
+        let mut to_collapse = to_collapse;
+
+        while !to_collapse.is_empty() {
+            let reduced = self.reduce_wave().map_err(SolverError::Contradiction)?;
+
+            for i in to_collapse {
+                self.state[i] = self.state[i].collapse();
+            }
+
+            to_collapse = reduced;
+        }
+
+        Ok(())
+    }
+
+    /// Reduces every unknown cell against its known neighbors, returning the
+    /// cells which were reduced to a single state or the index of a cell left
+    /// without any possible states
+    #[cfg(not(feature = "parallel"))]
+    fn reduce_wave(&mut self) -> Result<Vec<usize>, usize> {
+        let mut reduced = vec![];
+
+        for i in 0..S {
+            if let Some(reductions) = self.reduction(i) {
+                self.apply_reduction(i, reductions, &mut reduced)?;
+            }
+        }
+
+        Ok(reduced)
+    }
+
+    /// Reduces every unknown cell against its known neighbors, returning the
+    /// cells which were reduced to a single state or the index of a cell left
+    /// without any possible states.
+    ///
+    /// The reductions are computed in parallel against a snapshot of the
+    /// board and then applied in index order. A cell whose neighbors changed
+    /// earlier in the wave is recomputed, so the result matches a serial solve
+    #[cfg(feature = "parallel")]
+    fn reduce_wave(&mut self) -> Result<Vec<usize>, usize> {
+        use rayon::prelude::*;
+
+        let reductions = (0..S)
+            .into_par_iter()
+            .filter_map(|i| self.reduction(i).map(|reductions| (i, reductions)))
+            .collect::<Vec<(usize, BitArray<A, Lsb0>)>>();
+        let mut changed = [false; S];
+        let mut reduced = vec![];
+
+        for (i, reductions) in reductions {
+            let reductions = if self.neighbors.indices(i).iter().any(|&j| changed[j]) {
+                match self.reduction(i) {
+                    Some(reductions) => reductions,
+                    None => continue,
+                }
+            } else {
+                reductions
+            };
+
+            self.apply_reduction(i, reductions, &mut reduced)?;
+            changed[i] = self.state[i].is_reduced();
+        }
+
+        Ok(reduced)
+    }
+
+    /// The states the ith cell cannot be in, or `None` if the cell is already
+    /// known or its neighbors don't reduce it
+    fn reduction(&self, i: usize) -> Option<BitArray<A, Lsb0>> {
+        if !self.state[i].is_unknown() || self.frozen[i] {
+            return None;
+        }
+
+        let neighbors = self.known_neighbors(i);
+
+        if neighbors.is_empty() {
+            return None;
+        }
+
+        let mut reductions = (self.reducer)(neighbors, i).into_deny();
+        reductions[N..].fill(false);
+
+        if reductions.not_any() {
+            return None;
+        }
+
+        Some(reductions)
+    }
+
+    /// Removes the reductions from the ith cell, recording it in `reduced` if
+    /// it is left with a single state. Fails with the cell's index if it is
+    /// left without any possible states
+    fn apply_reduction(
+        &mut self,
+        i: usize,
+        reductions: BitArray<A, Lsb0>,
+        reduced: &mut Vec<usize>,
+    ) -> Result<(), usize> {
+        let before = self.state[i];
+        let result = before.reduce(reductions);
+
+        if result.is_changed() && self.explanations.is_some() {
+            let explanation = self.attribute(i, before.state());
+            if let Some(explanations) = &mut self.explanations {
+                explanations[i] = explanation;
+            }
+        }
+
+        self.state[i] = result.cell().ok_or(i)?;
+        log::trace!("Reduced {} to {:?}", i, self.state[i]);
+
+        if !self.state[i].same_state(&before) {
+            if let Some(touched) = &mut self.touched {
+                touched[i] = true;
+            }
+
+            if self.reasons.is_some() {
+                let reason = self.reason(i);
+                if let Some(reasons) = &mut self.reasons {
+                    reasons[i] = reason;
+                }
+            }
+
+            self.emit(SolverEvent::Reduce {
+                cell: i,
+                states: self.state[i].entropy(),
+            });
+        }
+
+        if self.state[i].is_reduced() {
+            reduced.push(i);
+        }
+
+        Ok(())
+    }
+
+    /// The states each known neighbor of the ith cell would remove from
+    /// `possible` on its own
+    fn attribute(&self, i: usize, possible: CellState<A>) -> Explanation<A> {
+        self.known_neighbors(i)
+            .into_iter()
+            .filter_map(|(j, cell)| {
+                let mut removed = (self.reducer)(vec![(j, cell)], i).into_deny() & possible;
+                removed[N..].fill(false);
+                removed.any().then_some((j, removed))
+            })
+            .collect()
+    }
+
+    /// Selects the cell to observe next, following the start strategy when
+    /// every cell is unknown
+    fn next_cell(&mut self) -> Option<usize> {
+        if S > 0 && self.state.iter().all(Cell::is_unknown) {
+            let start = match self.start {
+                StartStrategy::LowestEntropy => None,
+                StartStrategy::Random => Some(self.rng.gen_range(0..S)),
+                StartStrategy::Center => Some(S / 2),
+                StartStrategy::Index(i) => Some(i),
+            };
+
+            if let Some(i) = start.filter(|&i| !self.frozen[i]) {
+                return Some(i);
+            }
+        }
+
+        self.select_cell()
+    }
+
+    /// Randomly selects one cell with the lowest entropy, or the highest
+    /// under `EntropyHeuristic::Highest`. This is a single pass over the board
+    /// which draws from the rng once for each tie after the first, so ties
+    /// are picked uniformly without collecting them. Deterministic solvers
+    /// keep the first tie instead
+    fn select_cell(&mut self) -> Option<usize> {
+        let mut selected = None;
+        let mut best = 0;
+        let mut ties = 0;
+
+        for (i, cell) in self.state.iter().enumerate() {
+            if !cell.is_unknown() || cell.is_contradiction() || self.frozen[i] {
+                continue;
+            }
+
+            let entropy = cell.entropy();
+            let better = match self.heuristic {
+                EntropyHeuristic::Lowest => entropy < best,
+                EntropyHeuristic::Highest => entropy > best,
+            };
+
+            if selected.is_none() || better {
+                selected = Some(i);
+                best = entropy;
+                ties = 1;
+            } else if entropy == best && !self.deterministic {
+                ties += 1;
+                if self.rng.gen_range(0..ties) == 0 {
+                    selected = Some(i);
+                }
+            }
+        }
+
+        selected
+    }
+
+    /// Tries to solve a cell, if there is no solution it resets the board
+    fn observe(&mut self, i: usize) -> Result<Vec<usize>, SolverError> {
+        log::trace!("Observing {} {:?}", i, self.state[i]);
+
+        let observed = loop {
+            let observed = self.draw(i);
+            let (Ok((_, n)), Some(validate)) = (&observed, self.validate_observation) else {
+                break observed;
+            };
+
+            if validate(i, *n, &self.state) {
+                break observed;
+            }
+
+            log::trace!("Observing {} at {} was vetoed", n, i);
+            if !self.state[i].is_unknown() {
+                break Err(WeightedError::NoItem);
+            }
+
+            let mut vetoed = CellState::ZERO;
+            vetoed.set(*n, true);
+
+            match self.state[i].reduce(vetoed).cell() {
+                Some(cell) => self.state[i] = cell,
+                None => break Err(WeightedError::NoItem),
+            }
+        };
+
+        match observed {
+            Ok((cell, n)) => {
+                self.stats.observations += 1;
+                self.stats.observed_entropy += self.state[i].entropy();
+                self.clock += 1;
+                self.push_history(Frame {
+                    state: self.state,
+                    time: Some(self.clock),
+                    nogood: Some((i, n)),
+                });
+                self.state[i] = cell;
+
+                if let Some(reasons) = &mut self.reasons {
+                    reasons[i] = self.clock;
+                }
+                self.emit(SolverEvent::Observe { cell: i, value: n });
+
+                if let Some(on_collapse) = self.on_collapse {
+                    on_collapse(i, n, &self.state);
+                }
+
+                Ok(vec![i])
+            }
+            Err(WeightedError::NoItem) => {
+                // Vetoes may depend on any cell, so only the latest
+                // observation can be blamed
+                if let Some(reasons) = &mut self.reasons {
+                    reasons[i] = self.clock;
+                }
+
+                self.emit(SolverEvent::Contradiction { cell: i });
+                self.backtrack(Some(i))
+            }
+            Err(_) => Err(SolverError::WeightConfig(i)),
+        }
+    }
+
+    /// Randomly picks a possible state of the ith cell
+    fn draw(&mut self, i: usize) -> Result<(Cell<A, N>, usize), WeightedError> {
+        #[cfg(feature = "std")]
+        let tempered = self.temperature.is_some();
+        #[cfg(not(feature = "std"))]
+        let tempered = false;
+
+        let preferred = self.preferences.iter().any(|&(j, _, _)| j == i);
+
+        if self.directional_weights.is_some()
+            || self.dynamic_weights.is_some()
+            || tempered
+            || preferred
+        {
+            let weights = self.observation_weights(i);
+            self.state[i]
+                .observe_by(|n| weights[n], &mut self.rng)
+                .and_then(|cell| cell.value().map(|n| (cell, n)).ok_or(WeightedError::NoItem))
+        } else {
+            self.state[i].observe_value(self.weights, &mut self.rng)
+        }
+    }
+
+    /// The weight of each possible state of the ith cell after applying the
+    /// dynamic weights, directional weights, preferences, and temperature
+    fn observation_weights(&mut self, i: usize) -> [f64; N] {
+        let mut weights = [0.0; N];
+
+        for n in self.state[i].state().iter_ones().take_while(|&n| n < N) {
+            weights[n] = match &mut self.dynamic_weights {
+                Some(dynamic_weights) => dynamic_weights.weight(n, &self.state),
+                None => (self.weights)(&n) as f64,
+            };
+        }
+
+        if let Some(directional_weights) = self.directional_weights {
+            let directions = self.known_directions(i);
+            for (n, weight) in weights.iter_mut().enumerate() {
+                *weight *= directional_weights(n, &directions);
+            }
+        }
+
+        for &(_, n, weight) in self.preferences.iter().filter(|&&(j, _, _)| j == i) {
+            if let Some(preferred) = weights.get_mut(n) {
+                *preferred *= weight;
+            }
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(temperature) = self.temperature {
+            temper(&mut weights, temperature);
+        }
+
+        weights
+    }
+
+    /// Records a historic state, dropping the oldest once `max_history` is hit
+    fn push_history(&mut self, frame: Frame<A, N, S>) {
+        if matches!(self.recovery, Recovery::Restart(_)) {
+            return;
+        }
+
+        if let Some(max_history) = self.max_history {
+            while !self.history.is_empty() && self.history.len() >= max_history {
+                self.history.pop_front();
+            }
+        }
+
+        if self.max_history != Some(0) {
+            self.history.push_back(frame);
+        }
+    }
+
+    /// Restores the most recent historic state without the guesses which
+    /// failed from it, if there is none the board cannot be solved. When
+    /// backjumping, `conflict` is the cell which was left without any states
+    fn backtrack(&mut self, conflict: Option<usize>) -> Result<Vec<usize>, SolverError> {
+        if let Recovery::Restart(max_restarts) = self.recovery {
+            return self.restart(max_restarts);
+        }
+
+        if let (Recovery::BestEffort(fallback), Some(conflict)) = (self.recovery, conflict) {
+            return Ok(self.fall_back(conflict, fallback));
+        }
+
+        if let (Recovery::Backjump, Some(conflict)) = (self.recovery, conflict) {
+            self.backjump(conflict);
+        }
+
+        loop {
+            let depth = self.history.len();
+            log::trace!("Backtracking from a depth of {}", depth);
+            let Some(frame) = self.history.pop_back() else {
+                return match (self.recovery, conflict) {
+                    (Recovery::ClearRadius { radius, max_clears }, Some(conflict)) => {
+                        self.clear_radius(conflict, radius, max_clears)
+                    }
+                    _ => Err(SolverError::Unsolvable),
+                };
+            };
+
+            if let Some(state) = frame.restore() {
+                self.state = state;
+                self.stats.backtracks += 1;
+                self.emit(SolverEvent::Backtrack { depth });
+                return Ok(self.reduced());
+            }
+        }
+    }
+
+    /// Returns every cell within `radius` hops of `conflict` to its initial
+    /// state so the board can be re-solved around it, if no observations were
+    /// made since the last clear, or the solve has already cleared
+    /// `max_clears` times, the board cannot be solved
+    fn clear_radius(
+        &mut self,
+        conflict: usize,
+        radius: usize,
+        max_clears: usize,
+    ) -> Result<Vec<usize>, SolverError> {
+        if self.stats.observations == self.restart_mark || self.recoveries >= max_clears {
+            return Err(SolverError::Unsolvable);
+        }
+
+        self.recoveries += 1;
+        self.stats.clears += 1;
+        self.emit(SolverEvent::Clear {
+            cell: conflict,
+            radius,
+        });
+        self.restart_mark = self.stats.observations;
+
+        let mut cleared = [false; S];
+        let mut frontier = vec![conflict];
+        cleared[conflict] = true;
+
+        for _ in 0..radius {
+            frontier = frontier
+                .into_iter()
+                .flat_map(|i| self.neighbors.indices(i))
+                .filter(|&j| !mem::replace(&mut cleared[j], true))
+                .collect();
+        }
+
+        log::trace!(
+            "Clearing {} cells around {}",
+            cleared.iter().filter(|&&c| c).count(),
+            conflict
+        );
+        for i in (0..S).filter(|&i| cleared[i] && !self.frozen[i]) {
+            self.state[i] = self.initial[i];
+        }
+
+        Ok(self.reduced())
+    }
+
+    /// Collapses `conflict` to the fallback value so the solve can carry on
+    /// around it, returning the cells to propagate
+    fn fall_back(&mut self, conflict: usize, fallback: usize) -> Vec<usize> {
+        log::trace!("Falling back to {} at {}", fallback, conflict);
+        self.stats.fallbacks += 1;
+        self.emit(SolverEvent::Fallback {
+            cell: conflict,
+            value: fallback,
+        });
+        self.state[conflict] = Cell::collapsed(fallback);
+
+        let mut to_collapse = self.reduced();
+        to_collapse.push(conflict);
+        to_collapse
+    }
+
+    /// Drops every historic state more recent than the latest observation
+    /// the contradiction at `conflict` was derived from, so that observation
+    /// is the next undone. The observations skipped played no part in the
+    /// contradiction, so undoing them couldn't have avoided it. Nothing is
+    /// dropped if the culprit isn't in the history, such as when it was an
+    /// edit to the board, leaving an ordinary backtrack
+    fn backjump(&mut self, conflict: usize) {
+        let time = self.reason(conflict);
+        let culprit = self
+            .history
+            .iter()
+            .rposition(|frame| frame.time == Some(time));
+
+        if let Some(culprit) = culprit {
+            self.stats.backjumps += self.history.len() - culprit - 1;
+            self.history.truncate(culprit + 1);
+        }
+    }
+
+    /// Restores the initial state, if no observations were made since the
+    /// last restart, or the solve has already restarted `max_restarts` times,
+    /// the board cannot be solved
+    fn restart(&mut self, max_restarts: usize) -> Result<Vec<usize>, SolverError> {
+        if self.stats.observations == self.restart_mark || self.recoveries >= max_restarts {
+            return Err(SolverError::Unsolvable);
+        }
+
+        self.recoveries += 1;
+        self.stats.restarts += 1;
+        self.emit(SolverEvent::Restart);
+        self.restart_mark = self.stats.observations;
+        self.state = self.initial;
+        self.history.clear();
+        Ok(self.reduced())
+    }
+
+    /// The latest time the possible states of the ith cell or one of its
+    /// known neighbors were derived from, which are all the reducer is given
+    /// when reducing the cell. 0 when reasons aren't tracked
+    fn reason(&self, i: usize) -> usize {
+        let Some(reasons) = &self.reasons else {
+            return 0;
+        };
+
+        self.neighbors
+            .indices(i)
+            .into_iter()
+            .filter(|&j| !self.state[j].is_unknown())
+            .map(|j| reasons[j])
+            .fold(reasons[i], usize::max)
+    }
+
+    /// Moves the clock on and dates every cell to the new time after the
+    /// board was changed outside of a solve, since historic states from
+    /// before the change don't hold it
+    fn edit(&mut self) {
+        self.clock += 1;
+
+        if let Some(reasons) = &mut self.reasons {
+            *reasons = [self.clock; S];
+        }
+    }
+
+    /// Reports the event to the trace, if there is one
+    fn emit(&self, event: SolverEvent) {
+        if let Some(trace) = self.trace {
+            trace(event);
+        }
+    }
+
+    /// Checks the solver's invariants when debugging is enabled
+    fn debug_check(&self) {
+        if self.debug {
+            self.check_invariants();
+        }
+    }
+
+    /// Logs an error for each broken invariant and returns whether they all
+    /// held. Unknown cells must have several possible states, known cells
+    /// exactly one, and no cell may hold a state its latest historic state
+    /// had ruled out
+    fn check_invariants(&self) -> bool {
+        let mut valid = true;
+
+        for (i, cell) in self.state.iter().enumerate() {
+            let states = cell.state().count_ones();
+
+            if cell.is_unknown() && states < 2 {
+                log::error!("Unknown cell {} has {} possible states", i, states);
+                valid = false;
+            }
+
+            if !cell.is_unknown() && (states != 1 || cell.value().is_none_or(|n| n >= N)) {
+                log::error!("Known cell {} has an invalid state {:?}", i, cell);
+                valid = false;
+            }
+
+            if let Some(frame) = self.history.back() {
+                if (cell.state() & !frame.state[i].state()).any() {
+                    log::error!("Cell {} regained a state ruled out by its history", i);
+                    valid = false;
+                }
+            }
+        }
+
+        valid
+    }
+
+    /// Returns the neighbors of the ith cell which are no longer unknown
+    fn known_neighbors(&self, i: usize) -> Vec<(usize, &Cell<A, N>)> {
+        self.neighbors
+            .indices(i)
+            .iter()
+            .filter(|&&j| !self.state[j].is_unknown())
+            .map(|&j| (j, &self.state[j]))
+            .collect()
+    }
+
+    /// The directions of the ith cell's known neighbors, empty unless the
+    /// solver was built with directed neighbors or as a grid
+    fn known_directions(&self, i: usize) -> Vec<Direction> {
+        self.neighbors
+            .directed(i)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&(j, _)| !self.state[j].is_unknown())
+            .map(|(_, direction)| direction)
+            .collect()
+    }
+
+    fn reduced(&self) -> Vec<usize> {
+        self.state
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_reduced())
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>()
+    }
+}
+
+impl<
+        A: BitViewSized + Copy + Debug + MaybeSync,
+        const N: usize,
+        const S: usize,
+        R: Rng + Clone + MaybeSync,
+    > Solver<A, N, S, R>
+{
+    /// Captures the board, its history, and the position of the rng so a
+    /// speculative change can later be undone with `restore`
+    pub fn snapshot(&self) -> SolverSnapshot<A, N, S, R> {
+        SolverSnapshot {
+            state: self.state,
+            history: self.history.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Returns the board, its history, and the rng to how they were when
+    /// `snapshot` was taken
+    pub fn restore(&mut self, snapshot: SolverSnapshot<A, N, S, R>) {
+        self.state = snapshot.state;
+        self.history = snapshot.history;
+        self.rng = snapshot.rng;
+        self.edit();
+    }
+
+    /// The current position of the rng, which can be saved alongside the
+    /// board so a reloaded solver continues exactly where this one left off.
+    /// `StdRng` can't be serialized, so to save it build the solver with an
+    /// rng which can, like `rand_chacha::ChaCha12Rng` with its `serde1`
+    /// feature, passed to `SolverBuilder::rng`
+    pub fn rng_state(&self) -> R {
+        self.rng.clone()
+    }
+
+    /// Replaces the rng, such as with one saved from `rng_state`
+    pub fn set_rng_state(&mut self, rng: R) {
+        self.rng = rng;
+    }
+}
+
+impl<
+        A: BitViewSized + Copy + Debug + MaybeSync,
+        const N: usize,
+        const S: usize,
+        R: Rng + SeedableRng + MaybeSync,
+    > Solver<A, N, S, R>
+{
+    /// Solves the initial state with each seed in `range` and returns the
+    /// first whose solve succeeds within `budget`, leaving its solution and
+    /// stats in the solver. Combine with `SolverBuilder::timeout` to bound
+    /// the time spent on each seed. If no seed fits the budget the solver is
+    /// reset and `None` returned
+    /// ```
+    /// use wave_function_collapse::{puzzles::Sudoku, solver::{SolverBuilder, SolverStats}};
+    ///
+    /// type Puzzle = Sudoku<2, 2>;
+    ///
+    /// let mut solver = SolverBuilder::<u8, { Puzzle::SIDE }, { Puzzle::CELLS }>::new(
+    ///     Puzzle::neighbors,
+    ///     Puzzle::reducer,
+    /// )
+    /// .build()
+    /// .unwrap();
+    /// let budget = SolverStats { backtracks: 0, ..SolverStats::UNLIMITED };
+    /// let seed = solver.find_good_seed(0..64, budget);
+    /// assert!(seed.is_some());
+    /// ```
+    pub fn find_good_seed(&mut self, range: Range<u64>, budget: SolverStats) -> Option<u64> {
+        for seed in range {
+            self.reset();
+            self.rng = R::seed_from_u64(seed);
+            self.stats = SolverStats::default();
+            self.restart_mark = 0;
+
+            if self.solve().is_ok() && self.stats.within(&budget) {
+                log::debug!("Seed {} solved with {:?}", seed, self.stats);
+                return Some(seed);
+            }
+        }
+
+        self.reset();
+        None
+    }
+}
+
+/// A checkpoint of a solver, see `Solver::snapshot`
+#[derive(Debug, Clone)]
+pub struct SolverSnapshot<
+    A: BitViewSized + Copy + Debug,
+    const N: usize,
+    const S: usize,
+    R: Rng = StdRng,
+> {
+    state: SolverState<A, N, S>,
+    history: VecDeque<Frame<A, N, S>>,
+    rng: R,
+}
+
+impl<A: BitViewSized + Copy + Debug, const N: usize, const S: usize, R: Rng>
+    SolverSnapshot<A, N, S, R>
+{
+    /// The board when the snapshot was taken
+    pub fn state(&self) -> &SolverState<A, N, S> {
+        &self.state
+    }
+}
+
+/// A historic board state along with the guess, as an `(index, value)`
+/// pair, known to lead to a contradiction from it
+#[derive(Debug, Clone)]
+struct Frame<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> {
+    state: SolverState<A, N, S>,
+    /// The time of the observation made from this state, if any
+    time: Option<usize>,
+    nogood: Option<(usize, usize)>,
+}
+
+impl<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> Frame<A, N, S> {
+    fn new(state: SolverState<A, N, S>) -> Self {
+        Self {
+            state,
+            time: None,
+            nogood: None,
+        }
+    }
+
+    /// The state with the no-good removed, or `None` if that leaves a cell
+    /// without any possible states
+    fn restore(&self) -> Option<SolverState<A, N, S>> {
+        let mut state = self.state;
+
+        if let Some((i, n)) = self.nogood {
+            let mut reduction = BitArray::ZERO;
+            reduction.set(n, true);
+            state[i] = state[i].reduce(reduction).cell()?;
+        }
+
+        Some(state)
+    }
+}
+
+/// The progress of a solve spread across calls, see `Solver::solve_budget`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveStatus {
+    /// Some cells are still unknown
+    InProgress,
+    /// Every cell is known
+    Solved,
+    /// The solve failed and won't make further progress
+    Failed(SolverError),
+}
+
+/// The reasons a solve can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverError {
+    /// Every possibility was exhausted without finding a solution
+    Unsolvable,
+    /// The solve ran for longer than the configured timeout
+    Timeout,
+    /// The cell at the index was left without any possible states
+    Contradiction(usize),
+    /// The cell at the index couldn't be observed because none of its
+    /// possible states has a positive weight, which points to misconfigured
+    /// weights rather than a contradiction
+    WeightConfig(usize),
+    /// The solver couldn't be built, see `solve_grid`
+    Build(BuildError),
+}
+
+impl From<BuildError> for SolverError {
+    fn from(error: BuildError) -> Self {
+        Self::Build(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SolverError {}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Unsolvable => write!(f, "The board has no solution")?,
+            Self::Timeout => write!(f, "The solver ran out of time")?,
+            Self::Contradiction(i) => write!(f, "Cell {} has no possible states", i)?,
+            Self::WeightConfig(i) => write!(
+                f,
+                "Cell {} has no possible states with a positive weight",
+                i
+            )?,
+            Self::Build(e) => write!(f, "The solver could not be built: {}", e)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// An iterator over the solutions of a board, see `Solver::solutions`
+pub struct Solutions<
+    'a,
+    A: BitViewSized + Copy + Debug + MaybeSync,
+    const N: usize,
+    const S: usize,
+    R: Rng + MaybeSync,
+> {
+    solver: &'a mut Solver<A, N, S, R>,
+    recovery: Recovery,
+    started: bool,
+    done: bool,
+}
+
+impl<
+        A: BitViewSized + Copy + Debug + MaybeSync,
+        const N: usize,
+        const S: usize,
+        R: Rng + MaybeSync,
+    > Iterator for Solutions<'_, A, N, S, R>
+{
+    type Item = SolverState<A, N, S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        // Treat the previous solution as a contradiction to find the next
+        let to_collapse = if self.started {
+            self.solver.backtrack(None)
+        } else {
+            self.started = true;
+            Ok(self.solver.reduced())
+        };
+
+        match to_collapse.and_then(|to_collapse| self.solver.search(to_collapse, usize::MAX)) {
+            Ok(_) => Some(self.solver.state),
+            Err(_) => {
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+impl<
+        A: BitViewSized + Copy + Debug + MaybeSync,
+        const N: usize,
+        const S: usize,
+        R: Rng + MaybeSync,
+    > Drop for Solutions<'_, A, N, S, R>
+{
+    fn drop(&mut self) {
+        self.solver.recovery = self.recovery;
+    }
+}
+
+/// The reasons a solver can fail to build
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// The state at the index has a weight of 0 so can never be observed
+    ZeroWeight(usize),
+    /// The board cannot be split into rows of the length
+    RowLength(usize),
+    /// The border cell at the index cannot be the border's value
+    Border(usize),
+    /// The given index or value is outside the board or its states
+    Given(usize, usize),
+    /// The void cell at the index is outside the board
+    Void(usize),
+    /// The width and height don't multiply to the size of the board
+    Dimensions(usize, usize),
+    /// The fallback value of `Recovery::BestEffort` is not one of the states
+    Fallback(usize),
+    /// The start cell of `StartStrategy::Index` is outside the board
+    StartCell(usize),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::ZeroWeight(n) => write!(f, "State {} has a weight of 0", n)?,
+            Self::RowLength(n) => write!(f, "The board cannot be split into rows of {}", n)?,
+            Self::Border(i) => write!(f, "Border cell {} cannot be the border's value", i)?,
+            Self::Given(i, n) => write!(f, "Cell {} cannot be given the value {}", i, n)?,
+            Self::Void(i) => write!(f, "Void cell {} is outside the board", i)?,
+            Self::Dimensions(width, height) => {
+                write!(f, "The board is not {} by {} cells", width, height)?
+            }
+            Self::Fallback(n) => write!(f, "The fallback value {} is not a state", n)?,
+            Self::StartCell(i) => write!(f, "Start cell {} is outside the board", i)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Generates an endless board by sliding a window of `S` cells, `row_len`
+/// cells wide, across it. Cells which stay in view are kept as they are and
+/// only the newly exposed strip is solved against them
+pub struct InfiniteSolver<
+    A: BitViewSized + Copy + Debug,
+    const N: usize,
+    const S: usize,
+    R: Rng = StdRng,
+> {
+    solver: Solver<A, N, S, R>,
+    row_len: usize,
+    position: (isize, isize),
+}
+
+impl<
+        A: BitViewSized + Copy + Debug + MaybeSync,
+        const N: usize,
+        const S: usize,
+        R: Rng + MaybeSync,
+    > InfiniteSolver<A, N, S, R>
+{
+    /// Wraps a solver whose board is `row_len` cells wide
+    pub fn new(solver: Solver<A, N, S, R>, row_len: usize) -> Self {
+        Self {
+            solver,
+            row_len,
+            position: (0, 0),
+        }
+    }
+
+    pub fn solver(&self) -> &Solver<A, N, S, R> {
+        &self.solver
+    }
+
+    pub fn state(&self) -> &SolverState<A, N, S> {
+        self.solver.state()
+    }
+
+    /// The column and row of the window's top left cell, relative to where
+    /// it started
+    pub fn position(&self) -> (isize, isize) {
+        self.position
+    }
+
+    /// Fills in every unsolved cell in the window
+    pub fn solve(&mut self) -> Result<(), SolverError> {
+        self.solver.solve()
+    }
+
+    /// Pans the window and solves the newly exposed strip. Should the strip
+    /// have no solution against the cells bordering it, those cells are
+    /// released and solved again, one band at a time. Panning further than
+    /// the window is wide or tall exposes, and solves, the whole window
+    pub fn advance(&mut self, pan: Pan) -> Result<(), SolverError> {
+        let (distance, span) = match pan {
+            Pan::Left(distance) | Pan::Right(distance) => (distance, self.row_len),
+            Pan::Up(distance) | Pan::Down(distance) => (distance, S / self.row_len),
+        };
+        let distance = distance.min(span);
+        let (x, y) = self.position;
+        self.position = match pan {
+            Pan::Left(distance) => (x - distance as isize, y),
+            Pan::Right(distance) => (x + distance as isize, y),
+            Pan::Up(distance) => (x, y - distance as isize),
+            Pan::Down(distance) => (x, y + distance as isize),
+        };
+
+        self.solver.pan(pan, self.row_len);
+        let panned = self.solver.state;
+
+        for band in distance..=span {
+            if band > distance {
+                self.release(panned, pan, band);
+            }
+
+            match self.solver.solve() {
+                Err(SolverError::Unsolvable) if band < span => continue,
+                result => return result,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restores `panned` with every cell within `band` of the exposed edge
+    /// made unknown
+    fn release(&mut self, panned: SolverState<A, N, S>, pan: Pan, band: usize) {
+        let row_len = self.row_len;
+        let rows = S / row_len;
+        let exposed = |i: usize| match pan {
+            Pan::Left(_) => i % row_len < band,
+            Pan::Right(_) => row_len - i % row_len <= band,
+            Pan::Up(_) => i / row_len < band,
+            Pan::Down(_) => rows - i / row_len <= band,
+        };
+
+        self.solver.state = panned;
+
+        for i in (0..S).filter(|&i| exposed(i)) {
+            self.solver.state[i] = Cell::default();
+        }
+
+        self.solver.rebase();
+    }
+}
+
+/// How the solver recovers when a cell is left without any possible states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Recovery {
+    /// Undo the most recent observation
+    #[default]
+    Backtrack,
+    /// Discard every observation and start again from the initial state, at
+    /// most this many times per solve before failing with
+    /// `SolverError::Unsolvable`. This often converges faster than
+    /// backtracking for open ended generation, but can't tell a board with no
+    /// solution from an unlucky one, hence the limit
+    Restart(usize),
+    /// Undo the most recent observation the contradiction was derived from,
+    /// skipping more recent observations which played no part in it. Each
+    /// cell records the latest observation its possible states were derived
+    /// from, following chains of reductions, so no solution is skipped. This
+    /// can cut the search on structured puzzles like sudoku
+    Backjump,
+    /// Backtrack, but once the history is exhausted return every cell within
+    /// `radius` hops of the contradiction to its initial state, so givens are
+    /// kept, and carry on from there rather than failing. Useful for open
+    /// ended generation with a capped history, where a full restart would
+    /// throw away most of the board. The solve fails with
+    /// `SolverError::Unsolvable` after `max_clears` clears, or if no
+    /// observations were made since the last clear
+    ClearRadius { radius: usize, max_clears: usize },
+    /// Never backtrack, instead collapse each cell left without any possible
+    /// states to this fallback value and carry on, so every solve fills the
+    /// board even if some cells break the rules. Neighbors are reduced against
+    /// fallback cells like any other, so the value should be compatible with
+    /// as many states as possible, like an empty tile. Useful for decorative
+    /// output where a few conflicts are better than a failed solve
+    BestEffort(usize),
+}
+
+/// A step taken by the solver, see `SolverBuilder::trace`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverEvent {
+    /// The cell was observed to the value
+    Observe { cell: usize, value: usize },
+    /// Propagation removed states from the cell, leaving this many
+    Reduce { cell: usize, states: usize },
+    /// The cell was left without any possible states
+    Contradiction { cell: usize },
+    /// A historic state was restored, from a history this deep
+    Backtrack { depth: usize },
+    /// The board was restored to its initial state
+    Restart,
+    /// The cells within the radius of the cell were cleared
+    Clear { cell: usize, radius: usize },
+    /// The cell was collapsed to the fallback value
+    Fallback { cell: usize, value: usize },
+}
+
+/// Which cell is observed first when the board has no known cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StartStrategy {
+    /// Pick randomly among the cells with the lowest entropy, like any other
+    /// observation
+    #[default]
+    LowestEntropy,
+    /// Pick any cell uniformly at random
+    Random,
+    /// Pick the middle cell in index order, which is the center of a grid
+    /// with an odd width and height
+    Center,
+    /// Pick the cell at the index, `SolverBuilder::build` fails if it's
+    /// outside the board
+    Index(usize),
+}
+
+/// Which cells the solver observes once the board has known cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntropyHeuristic {
+    /// Observe the most constrained cells first, which keeps contradictions
+    /// rare
+    #[default]
+    Lowest,
+    /// Observe the least constrained cells first, spreading structure across
+    /// the board. This tends to contradict and backtrack far more often
+    Highest,
+}
+
+/// Counters describing the work done by a solver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SolverStats {
+    /// The number of cells observed
+    pub observations: usize,
+    /// The number of times a historic state was restored
+    pub backtracks: usize,
+    /// The number of times the solver started again from the initial state
+    pub restarts: usize,
+    /// The number of historic states skipped over by backjumping
+    pub backjumps: usize,
+    /// The number of times the cells around a contradiction were cleared
+    pub clears: usize,
+    /// The number of cells collapsed to the fallback value of
+    /// `Recovery::BestEffort`
+    pub fallbacks: usize,
+    /// The sum of the entropy of each observed cell just before it was
+    /// observed
+    pub observed_entropy: usize,
+}
+
+impl SolverStats {
+    /// Stats with every counter at its maximum, for building budgets which
+    /// only limit some counters, see `Solver::find_good_seed`
+    pub const UNLIMITED: Self = Self {
+        observations: usize::MAX,
+        backtracks: usize::MAX,
+        restarts: usize::MAX,
+        backjumps: usize::MAX,
+        clears: usize::MAX,
+        fallbacks: usize::MAX,
+        observed_entropy: usize::MAX,
+    };
+
+    /// Whether every counter is at most the budget's
+    pub fn within(&self, budget: &Self) -> bool {
+        self.observations <= budget.observations
+            && self.backtracks <= budget.backtracks
+            && self.restarts <= budget.restarts
+            && self.backjumps <= budget.backjumps
+            && self.clears <= budget.clears
+            && self.fallbacks <= budget.fallbacks
+            && self.observed_entropy <= budget.observed_entropy
+    }
+
+    /// The mean number of possible states of the cells chosen for observation,
+    /// or 0 if there were none. Lower values mean the solver is choosing more
+    /// constrained cells
+    pub fn branching_factor(&self) -> f64 {
+        if self.observations == 0 {
+            return 0.0;
+        }
+
+        self.observed_entropy as f64 / self.observations as f64
+    }
+}
+
+/// Counts of each kind of cell in a board, see `summarize`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateSummary<const N: usize> {
+    pub unknown: usize,
+    pub reduced: usize,
+    pub collapsed: usize,
+    /// The number of known cells, reduced or collapsed, holding each value
+    pub values: [usize; N],
+}
+
+/// The neighbors function a solver was built with
+enum NeighborFn {
+    Plain(Neighbors),
+    /// A neighbors closure, which unlike `Neighbors` may capture its
+    /// environment
+    Boxed(Box<dyn Fn(usize) -> Vec<usize> + Send + Sync>),
+    Directed(DirectedNeighbors),
+    /// A 4-connected grid of the width and height
+    Grid(usize, usize),
+    /// A 4-connected grid of the width and height whose edges wrap around
+    Torus(usize, usize),
+}
+
+impl NeighborFn {
+    /// The cells adjacent to the ith
+    fn indices(&self, i: usize) -> Vec<usize> {
+        match self {
+            Self::Plain(neighbors) => neighbors(i),
+            Self::Boxed(neighbors) => neighbors(i),
+            _ => self
+                .directed(i)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(j, _)| j)
+                .collect(),
+        }
+    }
+
+    /// The cells adjacent to the ith along with their directions, or `None`
+    /// if the neighbors have no directions
+    fn directed(&self, i: usize) -> Option<Vec<(usize, Direction)>> {
+        match *self {
+            Self::Plain(_) | Self::Boxed(_) => None,
+            Self::Directed(neighbors) => Some(neighbors(i)),
+            Self::Grid(width, height) => Some(grid_neighbors(i, width, height)),
+            Self::Torus(width, height) => Some(torus_neighbors(i, width, height)),
+        }
+    }
+}
+
+/// The direction and distance to pan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pan {
+    Left(usize),
+    Right(usize),
+    Up(usize),
+    Down(usize),
+}
+
+pub struct SolverBuilder<
+    A: BitViewSized + Copy + Debug,
+    const N: usize,
+    const S: usize,
+    R: Rng = StdRng,
+> {
+    seed: Option<u64>,
+    /// Creates the rng from the seed and whether the solver is deterministic
+    rng: Box<dyn FnOnce(Option<u64>, bool) -> R>,
+    state: Option<SolverState<A, N, S>>,
+    neighbors: NeighborFn,
+    reducer: StateReducer<A, N>,
+    weights: Option<Weights>,
+    directional_weights: Option<DirectionalWeights>,
+    dynamic_weights: Option<Box<dyn DynamicWeights<A, N, S>>>,
+    on_collapse: Option<OnCollapse<A, N, S>>,
+    validate_observation: Option<ValidateObservation<A, N, S>>,
+    trace: Option<fn(SolverEvent)>,
+    #[cfg(feature = "std")]
+    timeout: Option<Duration>,
+    #[cfg(feature = "std")]
+    temperature: Option<f64>,
+    max_history: Option<usize>,
+    history_capacity: usize,
+    recovery: Recovery,
+    start: StartStrategy,
+    heuristic: EntropyHeuristic,
+    debug: bool,
+    deterministic: bool,
+    deduce_first: bool,
+    validate_weights: bool,
+    explain: bool,
+    track_inert: bool,
+    border: Option<(usize, usize)>,
+    givens: Vec<(usize, usize)>,
+    void: Vec<usize>,
+    dimensions: Option<(usize, usize)>,
+}
+
+fn uniform(_: &usize) -> usize {
+    1
+}
+
+/// The seed used by unseeded solvers when the `thread_rng` feature is disabled
+pub const DEFAULT_SEED: u64 = 0;
+
+/// Hashes a named seed into a numeric one with 64 bit FNV-1a, which unlike
+/// `DefaultHasher` is stable across platforms and releases
+pub fn hash_seed(seed: &str) -> u64 {
+    seed.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Counts the unknown, reduced and collapsed cells in a board along with how
+/// many times each value appears
+pub fn summarize<A: BitViewSized + Copy + Debug, const N: usize, const S: usize>(
+    state: &SolverState<A, N, S>,
+) -> StateSummary<N> {
+    let mut summary = StateSummary {
+        unknown: 0,
+        reduced: 0,
+        collapsed: 0,
+        values: [0; N],
+    };
+
+    for cell in state {
+        match cell {
+            Cell::Unknown(_) => summary.unknown += 1,
+            Cell::Reduced(_, _) => summary.reduced += 1,
+            Cell::Collapsed(_, _) => summary.collapsed += 1,
+        }
+
+        if let Some(n) = cell.value() {
+            summary.values[n] += 1;
+        }
+    }
+
+    summary
+}
+
+/// Solves a board `width` cells wide and `height` cells tall in one call,
+/// returning the value of each cell. Unlike the builder, `neighbors` may be a
+/// closure capturing its environment. `givens` are applied like
+/// `SolverBuilder::givens`, and a board which can't be built fails with
+/// `SolverError::Build`
+/// ```
+/// use wave_function_collapse::{
+///     cell::{Cell, CellState},
+///     solver::solve_grid,
+/// };
+///
+/// let width = 3;
+/// // Each cell differs from the other cells in its row
+/// let row = move |i: usize| {
+///     (0..width)
+///         .map(|x| i / width * width + x)
+///         .filter(|&j| j != i)
+///         .collect()
+/// };
+/// let reducer = |neighbors: Vec<(usize, &Cell<u8, 3>)>, _| {
+///     let mut acc = CellState::<u8>::ZERO;
+///     for (_, cell) in neighbors {
+///         acc |= cell.state();
+///     }
+///     acc
+/// };
+///
+/// let values = solve_grid::<u8, 3, 6, _>(width, 2, row, reducer, &[(0, 2)]).unwrap();
+/// assert_eq!(values[0], 2);
+/// assert_ne!(values[1], values[2]);
+/// ```
+pub fn solve_grid<
+    A: BitViewSized + Copy + Debug + MaybeSync + 'static,
+    const N: usize,
+    const S: usize,
+    T: Into<Reduction<A>>,
+>(
+    width: usize,
+    height: usize,
+    neighbors: impl Fn(usize) -> Vec<usize> + Send + Sync + 'static,
+    reducer: impl Fn(Vec<(usize, &Cell<A, N>)>, usize) -> T + Send + Sync + 'static,
+    givens: &[(usize, usize)],
+) -> Result<Vec<usize>, SolverError> {
+    let mut solver = SolverBuilder::<A, N, S>::from_parts(
+        NeighborFn::Boxed(Box::new(neighbors)),
+        Box::new(move |neighbors: Vec<(usize, &Cell<A, N>)>, i: usize| {
+            reducer(neighbors, i).into()
+        }),
+    )
+    .dimensions(width, height)
+    .givens(givens)
+    .build()?;
+    solver.solve()?;
+
+    Ok(solver
+        .state()
+        .iter()
+        .map(|cell| cell.value().expect("A solved board has no unknown cells"))
+        .collect())
+}
+
+/// Raises each weight to the power of `1 / temperature`, or keeps only the
+/// heaviest weights when the temperature is at or below 0
+#[cfg(feature = "std")]
+fn temper(weights: &mut [f64], temperature: f64) {
+    if temperature <= 0.0 {
+        let heaviest = weights.iter().copied().fold(0.0, f64::max);
+        for weight in weights.iter_mut() {
+            *weight = if *weight > 0.0 && *weight == heaviest {
+                1.0
+            } else {
+                0.0
+            };
+        }
+    } else {
+        for weight in weights.iter_mut() {
+            *weight = weight.powf(1.0 / temperature);
+        }
+    }
+}
+
+/// Seeds an rng from `seed`, falling back to `DEFAULT_SEED` for deterministic
+/// solvers and `default_rng` otherwise
+fn seeded_rng<R: SeedableRng>(seed: Option<u64>, deterministic: bool) -> R {
+    match seed {
+        Some(seed) => R::seed_from_u64(seed),
+        None if deterministic => R::seed_from_u64(DEFAULT_SEED),
+        None => default_rng(),
+    }
+}
+
+/// Seeds an rng from the operating system's entropy
+#[cfg(feature = "thread_rng")]
+fn default_rng<R: SeedableRng>() -> R {
+    R::from_rng(rand::thread_rng()).unwrap()
+}
+
+/// Seeds an rng deterministically for targets without OS entropy
+#[cfg(not(feature = "thread_rng"))]
+fn default_rng<R: SeedableRng>() -> R {
+    R::seed_from_u64(DEFAULT_SEED)
+}
+
+impl<A: BitViewSized + Copy + Debug, const N: usize, const S: usize> SolverBuilder<A, N, S> {
+    /// Creates a builder from the neighbors of each cell and a reducer, which
+    /// may return either a `Reduction` or a bare deny-mask
+    pub fn new<T: Into<Reduction<A>>>(
+        neighbors: Neighbors,
+        reducer: impl Fn(Vec<(usize, &Cell<A, N>)>, usize) -> T + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_parts(
+            NeighborFn::Plain(neighbors),
+            Box::new(move |neighbors: Vec<(usize, &Cell<A, N>)>, i: usize| {
+                reducer(neighbors, i).into()
+            }),
+        )
+    }
+
+    /// Creates a builder from neighbors which know their direction from each
+    /// cell, so the reducer is given the direction of each known neighbor
+    /// rather than working it out from the indices
+    pub fn directed<T: Into<Reduction<A>>>(
+        neighbors: DirectedNeighbors,
+        reducer: impl Fn(Vec<(usize, Direction, &Cell<A, N>)>, usize) -> T + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_parts(
+            NeighborFn::Directed(neighbors),
+            Box::new(move |known: Vec<(usize, &Cell<A, N>)>, i: usize| {
+                let directions = neighbors(i);
+                let known = known
+                    .into_iter()
+                    .filter_map(|(j, cell)| {
+                        directions
+                            .iter()
+                            .find(|&&(k, _)| k == j)
+                            .map(|&(_, direction)| (j, direction, cell))
+                    })
+                    .collect();
+
+                reducer(known, i).into()
+            }),
+        )
+    }
+
+    /// Creates a builder whose reducer is composed from separate constraints,
+    /// forbidding every state forbidden by any of them, see
+    /// `constraint::compose`
+    pub fn constraints(neighbors: Neighbors, constraints: Vec<Box<dyn Constraint<A, N>>>) -> Self
+    where
+        A: 'static,
+    {
+        Self::from_parts(NeighborFn::Plain(neighbors), compose(constraints))
+    }
+
+    /// Creates a builder for a 4-connected grid `width` cells wide and
+    /// `height` cells tall, whose reducer is given the known neighbor on each
+    /// side of a cell. The builder's dimensions are set to the grid's
+    pub fn grid2d<T: Into<Reduction<A>>>(
+        width: usize,
+        height: usize,
+        reducer: impl Fn(NeighborsByDir<'_, A, N>, usize) -> T + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_parts(
+            NeighborFn::Grid(width, height),
+            Box::new(move |known: Vec<(usize, &Cell<A, N>)>, i: usize| {
+                let known = known
+                    .into_iter()
+                    .filter_map(|(j, cell)| {
+                        Direction::between_wrapping(i, j, width, height)
+                            .map(|direction| (j, direction, cell))
+                    })
+                    .collect();
+
+                reducer(NeighborsByDir::from_directed(known), i).into()
+            }),
+        )
+        .dimensions(width, height)
+    }
+
+    fn from_parts(neighbors: NeighborFn, reducer: StateReducer<A, N>) -> Self {
+        Self {
+            seed: None,
+            rng: Box::new(seeded_rng),
+            state: None,
+            neighbors,
+            reducer,
+            weights: None,
+            directional_weights: None,
+            dynamic_weights: None,
+            on_collapse: None,
+            validate_observation: None,
+            trace: None,
+            #[cfg(feature = "std")]
+            timeout: None,
+            #[cfg(feature = "std")]
+            temperature: None,
+            max_history: None,
+            history_capacity: 0,
+            recovery: Recovery::default(),
+            start: StartStrategy::default(),
+            heuristic: EntropyHeuristic::default(),
+            debug: false,
+            deterministic: false,
+            deduce_first: false,
+            validate_weights: false,
+            explain: false,
+            track_inert: false,
+            border: None,
+            givens: vec![],
+            void: vec![],
+            dimensions: None,
+        }
+    }
+}
+
+impl<A: BitViewSized + Copy + Debug, const N: usize, const S: usize, R: Rng>
+    SolverBuilder<A, N, S, R>
+{
+    /// Replaces the solver's rng with another generator, taking precedence
+    /// over `seed`. The generator needn't be seedable, though
+    /// `Solver::find_good_seed` is only available for seedable rngs
+    pub fn rng<T: Rng + 'static>(self, rng: T) -> SolverBuilder<A, N, S, T> {
+        SolverBuilder {
+            seed: self.seed,
+            rng: Box::new(move |_, _| rng),
+            state: self.state,
+            neighbors: self.neighbors,
+            reducer: self.reducer,
+            weights: self.weights,
+            directional_weights: self.directional_weights,
+            dynamic_weights: self.dynamic_weights,
+            on_collapse: self.on_collapse,
+            validate_observation: self.validate_observation,
+            trace: self.trace,
+            #[cfg(feature = "std")]
+            timeout: self.timeout,
+            #[cfg(feature = "std")]
+            temperature: self.temperature,
+            max_history: self.max_history,
+            history_capacity: self.history_capacity,
+            recovery: self.recovery,
+            start: self.start,
+            heuristic: self.heuristic,
+            debug: self.debug,
+            deterministic: self.deterministic,
+            deduce_first: self.deduce_first,
+            validate_weights: self.validate_weights,
+            explain: self.explain,
+            track_inert: self.track_inert,
+            border: self.border,
+            givens: self.givens,
+            void: self.void,
+            dimensions: self.dimensions,
+        }
+    }
+
+    /// Seeds the solver's rng. Without a seed the rng is drawn from
+    /// `thread_rng`, or from `DEFAULT_SEED` when that feature is disabled
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Seeds the solver's rng from a name like `"forest-42"`, hashed with
+    /// `hash_seed` so the same name gives the same board on every platform
+    pub fn seed_str(self, seed: &str) -> Self {
+        self.seed(hash_seed(seed))
+    }
+
+    pub fn state(mut self, state: SolverState<A, N, S>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Sets the width and height of the board, which `build` checks against
+    /// `S`, so the solver can `scroll` without being given the width
+    pub fn dimensions(mut self, width: usize, height: usize) -> Self {
+        self.dimensions = Some((width, height));
+        self
+    }
+
+    /// Wraps the edges of a grid built with `grid2d` around to the opposite
+    /// side, so the top row constrains the bottom and the left column the
+    /// right, and generated boards tile seamlessly. The reducer is given the
+    /// direction of wrapped neighbors as if the board were repeated. Has no
+    /// effect on other builders
+    pub fn toroidal(mut self) -> Self {
+        if let NeighborFn::Grid(width, height) = self.neighbors {
+            self.neighbors = NeighborFn::Torus(width, height);
+        }
+        self
+    }
+
+    /// Carves the cells at `indices` out of the board, for shapes which aren't
+    /// rectangular. Void cells are never observed, reduced, or panned, are
+    /// never given to the reducer, and don't count as unsolved. `build` fails
+    /// if an index is outside the board
+    pub fn void(mut self, indices: &[usize]) -> Self {
+        self.void.extend_from_slice(indices);
+        self
+    }
+
+    /// Reduces each `(index, value)` cell of the initial state to the value,
+    /// on top of `state` if one was given, so sparse puzzles needn't spell
+    /// out every cell. `build` fails if an index or value is out of range
+    pub fn givens(mut self, givens: &[(usize, usize)]) -> Self {
+        self.givens.extend_from_slice(givens);
+        self
+    }
+
+    pub fn weights(mut self, weights: Weights) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Scales the weight of each state by `directional_weights`, which is
+    /// given the directions of the observed cell's known neighbors. This biases
+    /// generation towards a preferred flow, like favouring horizontal
+    /// corridors. The directions are only known to solvers built with
+    /// `SolverBuilder::directed`, otherwise they are always empty
+    pub fn directional_weights(mut self, directional_weights: DirectionalWeights) -> Self {
+        self.directional_weights = Some(directional_weights);
+        self
+    }
+
+    /// Replaces `weights` with weights computed from the board at each
+    /// observation, for distributions static weights can't express like
+    /// avoiding repetition. Directional weights, preferences and temperature
+    /// are applied on top
+    pub fn dynamic_weights(
+        mut self,
+        dynamic_weights: impl DynamicWeights<A, N, S> + 'static,
+    ) -> Self {
+        self.dynamic_weights = Some(Box::new(dynamic_weights));
+        self
+    }
+
+    /// Calls `on_collapse` after each observation with the index and value of
+    /// the observed cell. Cells reduced by propagation are not reported
+    pub fn on_collapse(mut self, on_collapse: OnCollapse<A, N, S>) -> Self {
+        self.on_collapse = Some(on_collapse);
+        self
+    }
+
+    /// Calls `validate` with each value the solver picks for a cell before
+    /// observing it, for global rules like a limit on how many times a tile
+    /// appears. A vetoed value is ruled out for the cell and another is
+    /// picked, once none are left the solver backtracks
+    pub fn validate_observation(mut self, validate: ValidateObservation<A, N, S>) -> Self {
+        self.validate_observation = Some(validate);
+        self
+    }
+
+    /// Calls `trace` with each observation, reduction, contradiction and
+    /// recovery as it happens, for logging or visualizing how a solve
+    /// progressed. Events are reported synchronously, so `trace` can timestamp
+    /// them itself. Without a trace no events are built
+    pub fn trace(mut self, trace: fn(SolverEvent)) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Limits how long a single call to `solve` may run for before it fails
+    /// with `SolverError::Timeout`. The time is only checked between
+    /// observations, so a solve may overrun by one propagation
+    #[cfg(feature = "std")]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Controls how surprising observations are by raising each weight to the
+    /// power of `1 / temperature`. At 1 the weights are used as is, higher
+    /// temperatures flatten them towards a uniform choice, and at 0 the
+    /// heaviest possible state is always chosen, breaking ties randomly
+    #[cfg(feature = "std")]
+    pub fn temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Caps the number of historic states kept for backtracking, dropping the
+    /// oldest once the cap is hit. This bounds memory use on large boards, but
+    /// the decisions behind dropped states can no longer be undone, so a solve
+    /// may fail with `SolverError::Unsolvable` even though a solution exists
+    pub fn max_history(mut self, max_history: usize) -> Self {
+        self.max_history = Some(max_history);
+        self
+    }
+
+    /// Allocates room for this many historic states up front, so deep solves
+    /// don't reallocate the history mid-solve. Each historic state is a copy
+    /// of the board, so `S` states hold `S * S` cells. Capped at
+    /// `max_history`
+    pub fn history_capacity(mut self, capacity: usize) -> Self {
+        self.history_capacity = capacity;
+        self
+    }
+
+    /// Sets how the solver recovers from a contradiction, defaulting to
+    /// `Recovery::Backtrack`
+    pub fn recovery(mut self, recovery: Recovery) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    /// Sets which cell is observed first when the board has no known cells,
+    /// defaulting to `StartStrategy::LowestEntropy`
+    pub fn start_cell(mut self, start: StartStrategy) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Sets whether the cells with the lowest or highest entropy are observed,
+    /// defaulting to `EntropyHeuristic::Lowest`
+    pub fn entropy_heuristic(mut self, heuristic: EntropyHeuristic) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// Makes the board depend only on the seed and input, so puzzles can be
+    /// regenerated identically on any machine. Ties between cells of equal
+    /// entropy go to the lowest index rather than being drawn from the rng,
+    /// and an unseeded solver uses `DEFAULT_SEED` rather than `thread_rng`.
+    /// Entropy is always compared as a whole number of states, and `StdRng`
+    /// is ChaCha12, whose output is fixed for a given version of `rand`.
+    /// Temperature and directional weights use floating point, so leave them
+    /// unset for output which must match across platforms
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// Checks the solver's invariants after each observation, logging any
+    /// which are broken through the `log` crate. This is useful for
+    /// diagnosing custom reducers, but slows down every solve
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Makes `build` check that every state has a positive weight, since a
+    /// state with a weight of 0 can never be observed and a cell left with
+    /// only such states fails mid-solve
+    pub fn validate_weights(mut self, validate_weights: bool) -> Self {
+        self.validate_weights = validate_weights;
+        self
+    }
+
+    /// Reduces every cell on the edge of a board `row_len` cells wide to
+    /// `value` when building, like a wall around a maze. `build` fails if the
+    /// board can't be split into rows of `row_len` or a known border cell has
+    /// a different value
+    pub fn border(mut self, value: usize, row_len: usize) -> Self {
+        self.border = Some((value, row_len));
+        self
+    }
+
+    /// Propagates the known cells to a fixpoint, like
+    /// `Solver::propagate_to_fixpoint`, before the first observation of each
+    /// solve. Every cell the givens imply is filled in by logic alone, and
+    /// givens which contradict each other fail with
+    /// `SolverError::Contradiction` rather than being searched
+    pub fn deduce_first(mut self, deduce_first: bool) -> Self {
+        self.deduce_first = deduce_first;
+        self
+    }
+
+    /// Records which neighbors removed states from each cell during
+    /// propagation, so they can be looked up with `Solver::explain`. This calls
+    /// the reducer again for each known neighbor of every reduced cell
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Records which cells the reducer removes states from, so cells it never
+    /// constrains can be found with `Solver::inert_cells`
+    pub fn track_inert(mut self, track_inert: bool) -> Self {
+        self.track_inert = track_inert;
+        self
+    }
+
+    pub fn build(self) -> Result<Solver<A, N, S, R>, BuildError> {
+        let mut state = match self.state {
+            Some(state) => state,
+            None => [Cell::default(); S],
+        };
+
+        if let Some((width, height)) = self.dimensions {
+            if width.checked_mul(height) != Some(S) {
+                return Err(BuildError::Dimensions(width, height));
+            }
+        }
+
+        for &(i, value) in &self.givens {
+            if i >= S || value >= N {
+                return Err(BuildError::Given(i, value));
+            }
+
+            state[i] = Cell::reduced(value);
+        }
+
+        let mut void = [false; S];
+
+        for &i in &self.void {
+            if i >= S {
+                return Err(BuildError::Void(i));
+            }
+
+            void[i] = true;
+            state[i] = Cell::default();
+        }
+
+        let frozen = void;
+
+        if let Some((value, row_len)) = self.border {
+            if row_len == 0 || !S.is_multiple_of(row_len) {
+                return Err(BuildError::RowLength(row_len));
+            }
+
+            let rows = S / row_len;
+            let border = (0..S).filter(|&i| !void[i]).filter(|i| {
+                let (x, y) = (i % row_len, i / row_len);
+                x == 0 || x == row_len - 1 || y == 0 || y == rows - 1
+            });
+
+            for i in border {
+                if state[i].collapse_to(value).is_none() {
+                    return Err(BuildError::Border(i));
+                }
+
+                if state[i].is_unknown() {
+                    state[i] = Cell::reduced(value);
+                }
+            }
+        }
+
+        if let StartStrategy::Index(i) = self.start {
+            if i >= S {
+                return Err(BuildError::StartCell(i));
+            }
+        }
+
+        if let Recovery::BestEffort(fallback) = self.recovery {
+            if fallback >= N {
+                return Err(BuildError::Fallback(fallback));
+            }
+        }
+
+        let weights = self.weights.unwrap_or(uniform);
+
+        if self.validate_weights {
+            if let Some(n) = (0..N).find(|n| weights(n) == 0) {
+                return Err(BuildError::ZeroWeight(n));
+            }
+        }
+
+        Ok(Solver {
+            state,
+            history: VecDeque::with_capacity(
+                self.max_history
+                    .map_or(self.history_capacity, |max| max.min(self.history_capacity)),
+            ),
+            max_history: self.max_history,
+            neighbors: self.neighbors,
+            reducer: self.reducer,
+            weights,
+            directional_weights: self.directional_weights,
+            dynamic_weights: self.dynamic_weights,
+            rng: (self.rng)(self.seed, self.deterministic),
+            on_collapse: self.on_collapse,
+            validate_observation: self.validate_observation,
+            trace: self.trace,
+            #[cfg(feature = "std")]
+            timeout: self.timeout,
+            #[cfg(feature = "std")]
+            temperature: self.temperature,
+            recovery: self.recovery,
+            start: self.start,
+            heuristic: self.heuristic,
+            debug: self.debug,
+            deterministic: self.deterministic,
+            deduce_first: self.deduce_first,
+            initial: state,
+            restart_mark: 0,
+            recoveries: 0,
+            stats: SolverStats::default(),
+            frozen,
+            void,
+            dimensions: self.dimensions,
+            preferences: vec![],
+            explanations: self.explain.then(|| vec![vec![]; S]),
+            touched: self.track_inert.then_some([false; S]),
+            clock: 0,
+            reasons: (self.recovery == Recovery::Backjump).then_some([0; S]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATES: usize = 4;
+    const ROW_LEN: usize = 4;
+    const BOARD_SIZE: usize = ROW_LEN * ROW_LEN;
+
+    type Storage = u8;
+    type State = BitArray<Storage, Lsb0>;
+    type TestCell = Cell<Storage, STATES>;
+    type TestSolver = Solver<Storage, STATES, BOARD_SIZE>;
+
+    /// Every other cell in the same row and column
+    fn neighbors(i: usize) -> Vec<usize> {
+        let x = i % ROW_LEN;
+        let y = i / ROW_LEN;
+
+        (0..ROW_LEN)
+            .map(|j| y * ROW_LEN + j)
+            .chain((0..ROW_LEN).map(|j| j * ROW_LEN + x))
+            .filter(|&j| j != i)
+            .collect()
+    }
+
+    /// A cell cannot share a value with any of its neighbors
+    fn reducer(neighbors: Vec<(usize, &TestCell)>, _: usize) -> State {
+        let mut acc = State::ZERO;
+
+        for (_, cell) in neighbors {
+            acc |= cell.state();
+        }
+
+        acc
+    }
+
+    /// The cells either side of a cell in the same row
+    fn row_neighbors(i: usize) -> Vec<(usize, Direction)> {
+        let mut neighbors = vec![];
+        if !i.is_multiple_of(ROW_LEN) {
+            neighbors.push((i - 1, Direction::Left));
+        }
+        if i % ROW_LEN < ROW_LEN - 1 {
+            neighbors.push((i + 1, Direction::Right));
+        }
+        neighbors
+    }
+
+    fn givens(givens: &[(usize, usize)]) -> SolverState<Storage, STATES, BOARD_SIZE> {
+        let mut state = [TestCell::default(); BOARD_SIZE];
+        for &(i, n) in givens {
+            state[i] = TestCell::reduced(n);
+        }
+        state
+    }
+
+    #[test]
+    fn solve_empty_board() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Ok(()));
+        assert!(solver.state().iter().all(Cell::is_collapsed));
+        assert_eq!(solver.check_initial(), Ok(()));
+    }
+
+    #[test]
+    fn solve_unsolvable() {
+        let mut solver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Err(SolverError::Unsolvable));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn solve_timeout() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .timeout(Duration::ZERO)
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Err(SolverError::Timeout));
+    }
+
+    #[test]
+    fn entropy_maps() {
+        let solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0)]))
+            .build()
+            .unwrap();
+        let entropy = solver.entropy_map();
+        assert_eq!(entropy[0], 1);
+        assert!(entropy[1..].iter().all(|&e| e == STATES));
+
+        #[cfg(feature = "std")]
+        {
+            let entropy = solver.entropy_map_f64();
+            assert_eq!(entropy[0], 0.0);
+            assert!(entropy[1..]
+                .iter()
+                .all(|&e| (e - (STATES as f64).ln()).abs() < 1e-9));
+        }
+    }
+
+    /// A generator which can't be seeded, so can only be given to a builder
+    struct CounterRng(u64);
+
+    impl rand::RngCore for CounterRng {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_u64() >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    /// Solvers built from identical rngs make identical choices
+    fn solve_custom_rng() {
+        let solve = || {
+            let mut solver: Solver<Storage, STATES, BOARD_SIZE, CounterRng> =
+                SolverBuilder::new(neighbors, reducer)
+                    .rng(CounterRng(7))
+                    .build()
+                    .unwrap();
+            solver.solve().unwrap();
+            solver.state().map(|cell| cell.value())
+        };
+        assert_eq!(solve(), solve());
+    }
+
+    #[test]
+    fn history_capped() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .max_history(2)
+            .build()
+            .unwrap();
+        assert_eq!(solver.history_depth(), 0);
+        solver.solve().unwrap();
+        assert!(solver.history_depth() <= 2);
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert!(solver.history_depth() > 2);
+    }
+
+    #[test]
+    fn pan_without_history() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(5, 2)]))
+            .max_history(0)
+            .build()
+            .unwrap();
+        solver.pan(Pan::Left(1), ROW_LEN);
+        assert_eq!(solver.state(), &givens(&[(6, 2)]));
+        assert_eq!(solver.history_depth(), 0);
+    }
+
+    #[test]
+    fn history_preallocated() {
+        let solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .history_capacity(BOARD_SIZE)
+            .build()
+            .unwrap();
+        assert!(solver.history.capacity() >= BOARD_SIZE);
+
+        let solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .history_capacity(BOARD_SIZE)
+            .max_history(2)
+            .build()
+            .unwrap();
+        assert!(solver.history.capacity() < BOARD_SIZE);
+    }
+
+    #[test]
+    /// Backtracking past a guess rules it out rather than retrying it
+    fn backtrack_forbids_guess() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.observe(0).unwrap();
+        let guess = solver.state()[0].value().unwrap();
+
+        solver.backtrack(None).unwrap();
+        assert!(solver.state()[0].is_unknown());
+        assert!(!solver.state()[0].state()[guess]);
+        assert_eq!(solver.history_depth(), 0);
+    }
+
+    #[test]
+    /// Only observations are reported, not the cells reduced by propagation
+    fn on_collapse_observations() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static COLLAPSES: AtomicUsize = AtomicUsize::new(0);
+
+        fn on_collapse(i: usize, n: usize, state: &SolverState<Storage, STATES, BOARD_SIZE>) {
+            assert_eq!(state[i].value(), Some(n));
+            COLLAPSES.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .on_collapse(on_collapse)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        let collapses = COLLAPSES.load(Ordering::Relaxed);
+        assert_eq!(collapses, solver.stats().observations);
+        assert!(collapses < BOARD_SIZE);
+    }
+
+    #[test]
+    fn trace_events() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static OBSERVES: AtomicUsize = AtomicUsize::new(0);
+        static REDUCES: AtomicUsize = AtomicUsize::new(0);
+        static FALLBACKS: AtomicUsize = AtomicUsize::new(0);
+
+        fn trace(event: SolverEvent) {
+            match event {
+                SolverEvent::Observe { .. } => OBSERVES.fetch_add(1, Ordering::Relaxed),
+                SolverEvent::Reduce { states, .. } => {
+                    assert!(states > 0);
+                    REDUCES.fetch_add(1, Ordering::Relaxed)
+                }
+                SolverEvent::Fallback { value, .. } => {
+                    assert_eq!(value, 0);
+                    FALLBACKS.fetch_add(1, Ordering::Relaxed)
+                }
+                _ => 0,
+            };
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .recovery(Recovery::BestEffort(0))
+            .trace(trace)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_eq!(
+            OBSERVES.load(Ordering::Relaxed),
+            solver.stats().observations
+        );
+        assert_eq!(FALLBACKS.load(Ordering::Relaxed), solver.stats().fallbacks);
+        assert!(REDUCES.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    /// Only backtracks which restored a historic state are reported
+    fn trace_backtracks() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static BACKTRACKS: AtomicUsize = AtomicUsize::new(0);
+
+        fn trace(event: SolverEvent) {
+            if let SolverEvent::Backtrack { depth } = event {
+                assert!(depth > 0);
+                BACKTRACKS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(3, 3), (5, 0), (10, 3), (12, 3)]))
+            .seed(0)
+            .trace(trace)
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Err(SolverError::Unsolvable));
+        assert!(BACKTRACKS.load(Ordering::Relaxed) > 0);
+        assert_eq!(
+            BACKTRACKS.load(Ordering::Relaxed),
+            solver.stats().backtracks
+        );
+    }
+
+    #[test]
+    /// State 0 may never be observed, so it only appears where propagation
+    /// forces it
+    fn validate_observations() {
+        fn validate(_: usize, n: usize, _: &SolverState<Storage, STATES, BOARD_SIZE>) -> bool {
+            n != 0
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .validate_observation(validate)
+            .trace(|event| {
+                if let SolverEvent::Observe { value, .. } = event {
+                    assert_ne!(value, 0);
+                }
+            })
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_eq!(solver.check_initial(), Ok(()));
+
+        // Once every value has been vetoed the solver gives up
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .validate_observation(|_, _, _| false)
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Err(SolverError::Unsolvable));
+        assert_eq!(solver.stats().observations, 0);
+    }
+
+    #[test]
+    /// A cell without any possible states is never observed
+    fn contradiction_unsolvable() {
+        let mut state = [TestCell::default(); BOARD_SIZE];
+        state[3] = TestCell::Unknown(State::ZERO);
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(state)
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Err(SolverError::Unsolvable));
+        assert_eq!(solver.stats().observations, 0);
+    }
+
+    #[test]
+    fn solve_grid_closures() {
+        let row_len = ROW_LEN;
+        let values = solve_grid::<Storage, STATES, BOARD_SIZE, _>(
+            ROW_LEN,
+            ROW_LEN,
+            move |i| {
+                neighbors(i)
+                    .into_iter()
+                    .filter(|j| j / row_len == i / row_len)
+                    .collect()
+            },
+            reducer,
+            &[(0, 3)],
+        )
+        .unwrap();
+        assert_eq!(values.len(), BOARD_SIZE);
+        assert_eq!(values[0], 3);
+        assert!(values[1..ROW_LEN].iter().all(|&n| n != 3));
+
+        let build = solve_grid::<Storage, STATES, BOARD_SIZE, _>(3, 3, neighbors, reducer, &[]);
+        assert_eq!(build, Err(SolverError::Build(BuildError::Dimensions(3, 3))));
+    }
+
+    #[test]
+    fn verify_against_reducer() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_eq!(solver.verify(), Ok(()));
+
+        let solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .givens(&[(0, 1), (2, 1), (5, 3)])
+            .build()
+            .unwrap();
+        assert_eq!(solver.verify(), Err(vec![0, 2]));
+    }
+
+    #[test]
+    /// Each cell only lists the cell to its left, so propagation runs left to
+    /// right across each row and never back
+    fn asymmetric_neighbors() {
+        fn left_of(i: usize) -> Vec<usize> {
+            if !i.is_multiple_of(ROW_LEN) {
+                vec![i - 1]
+            } else {
+                vec![]
+            }
+        }
+
+        fn count_up(neighbors: Vec<(usize, &TestCell)>, _: usize) -> Reduction<Storage> {
+            match neighbors.first().and_then(|(_, cell)| cell.value()) {
+                Some(n) => Reduction::Force((n + 1) % STATES),
+                None => Reduction::Deny(State::ZERO),
+            }
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(left_of, count_up)
+            .givens(&[(0, 2), (4, 0), (8, 1), (12, 3)])
+            .build()
+            .unwrap();
+        assert_eq!(solver.propagate_to_fixpoint(), Ok(true));
+        for (i, cell) in solver.state().iter().enumerate() {
+            let first = [2, 0, 1, 3][i / ROW_LEN];
+            assert_eq!(cell.value(), Some((first + i % ROW_LEN) % STATES));
+        }
+        assert_eq!(solver.verify(), Ok(()));
+
+        let mut solver: TestSolver = SolverBuilder::new(left_of, count_up)
+            .givens(&[(3, 0)])
+            .build()
+            .unwrap();
+        assert_eq!(solver.propagate_to_fixpoint(), Ok(false));
+        assert_eq!(solver.unsolved_count(), BOARD_SIZE - 1);
+    }
+
+    #[test]
+    fn solve_first_row() {
+        let row = [0, 1, 2, 3];
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(5, 0), (10, 1)]))
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve_region(&row).unwrap();
+        for (i, cell) in solver.state().iter().enumerate() {
+            match i {
+                0..=3 => assert!(cell.value().is_some()),
+                5 => assert_eq!(cell, &TestCell::reduced(0)),
+                10 => assert_eq!(cell, &TestCell::reduced(1)),
+                _ => assert!(cell.is_unknown()),
+            }
+        }
+        assert_eq!(solver.verify(), Ok(()));
+        assert_eq!(solver.history_depth(), 0);
+
+        // The first cell's row and column already hold every value
+        let state = givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]);
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(state)
+            .recovery(Recovery::Restart(100))
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve_region(&[0, 3]), Err(SolverError::Unsolvable));
+        assert_eq!(solver.state(), &state);
+    }
+
+    #[test]
+    fn deduce_before_observing() {
+        let build = |deduce_first| -> TestSolver {
+            SolverBuilder::new(neighbors, reducer)
+                .state(givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]))
+                .deduce_first(deduce_first)
+                .build()
+                .unwrap()
+        };
+        assert_eq!(build(true).solve(), Err(SolverError::Contradiction(0)));
+        assert_eq!(build(false).solve(), Err(SolverError::Unsolvable));
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0), (1, 1), (2, 2), (4, 1), (8, 2), (5, 2)]))
+            .deduce_first(true)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_eq!(solver.check_initial(), Ok(()));
+    }
+
+    #[test]
+    fn start_cell_strategies() {
+        let first = |start| {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .start_cell(start)
+                .build()
+                .unwrap();
+            solver.solve_n(1).unwrap();
+            solver
+                .state()
+                .iter()
+                .position(|cell| !cell.is_unknown())
+                .unwrap()
+        };
+
+        assert_eq!(first(StartStrategy::Index(5)), 5);
+        assert_eq!(first(StartStrategy::Center), BOARD_SIZE / 2);
+        assert!(first(StartStrategy::Random) < BOARD_SIZE);
+
+        let build = SolverBuilder::<Storage, STATES, BOARD_SIZE>::new(neighbors, reducer)
+            .start_cell(StartStrategy::Index(BOARD_SIZE))
+            .build();
+        assert_eq!(build.err(), Some(BuildError::StartCell(BOARD_SIZE)));
+    }
+
+    #[test]
+    fn invariants_checked() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .debug(true)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert!(solver.check_invariants());
+
+        solver.state[0] = Cell::Unknown(solver.state[0].state());
+        assert!(!solver.check_invariants());
+    }
+
+    #[test]
+    fn progress_counts() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0), (5, 1)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.unsolved_count(), BOARD_SIZE - 2);
+        assert_eq!(solver.progress(), 2.0 / BOARD_SIZE as f64);
+
+        solver.solve().unwrap();
+        assert_eq!(solver.unsolved_count(), 0);
+        assert_eq!(solver.progress(), 1.0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn estimated_progress_entropy() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        assert_eq!(solver.estimated_progress(), 0.0);
+
+        // Neighbors which are only reduced still count towards the progress
+        solver.assign(0, 0).unwrap();
+        let partial = solver.estimated_progress();
+        assert!(partial > solver.progress() && partial < 1.0);
+
+        solver.solve().unwrap();
+        assert_eq!(solver.estimated_progress(), 1.0);
+    }
+
+    #[test]
+    fn scroll_with_dimensions() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(5, 2)]))
+            .dimensions(ROW_LEN, ROW_LEN)
+            .build()
+            .unwrap();
+        assert_eq!(solver.dimensions(), Some((ROW_LEN, ROW_LEN)));
+        solver.scroll(Pan::Down(1));
+        assert_eq!(solver.state(), &givens(&[(1, 2)]));
+
+        let build = SolverBuilder::<Storage, STATES, BOARD_SIZE>::new(neighbors, reducer)
+            .dimensions(ROW_LEN, ROW_LEN + 1)
+            .build();
+        assert_eq!(
+            build.err(),
+            Some(BuildError::Dimensions(ROW_LEN, ROW_LEN + 1))
+        );
+    }
+
+    #[test]
+    fn pan_diagonally() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(5, 2), (15, 3)]))
+            .build()
+            .unwrap();
+        solver.pan_xy(1, 1, ROW_LEN);
+        let known = (0..BOARD_SIZE)
+            .filter_map(|i| solver.state()[i].value().map(|n| (i, n)))
+            .collect::<Vec<_>>();
+        assert_eq!(known, [(0, 2), (10, 3)]);
+
+        solver.pan_xy(-1, -1, ROW_LEN);
+        assert_eq!(solver.state(), &givens(&[(5, 2), (15, 3)]));
+
+        // Panning left keeps every column which stays on the board
+        solver.pan(Pan::Left(1), ROW_LEN);
+        assert_eq!(solver.state(), &givens(&[(6, 2)]));
+        assert_eq!(solver.history_depth(), 1);
+    }
+
+    #[test]
+    fn supports_count_neighbor_states() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer).build().unwrap();
+        // Each of the 6 neighbors has 3 states which differ from any state
+        assert_eq!(solver.supports(0), [18; STATES]);
+
+        solver.assign(1, 0).unwrap();
+        // The row has lost 0 while the column still has every state
+        assert_eq!(solver.supports(0), [0, 14, 14, 14]);
+    }
+
+    #[test]
+    fn solve_over_budgets() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        let mut calls = 1;
+        while solver.solve_budget(1) == SolveStatus::InProgress {
+            calls += 1;
+        }
+
+        assert!(calls > 1);
+        assert_eq!(solver.solve_budget(1), SolveStatus::Solved);
+        assert_eq!(solver.check_initial(), Ok(()));
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]))
+            .build()
+            .unwrap();
+        let mut status = SolveStatus::InProgress;
+        while status == SolveStatus::InProgress {
+            status = solver.solve_budget(1);
+        }
+        assert_eq!(status, SolveStatus::Failed(SolverError::Unsolvable));
+    }
+
+    #[test]
+    fn build_with_givens() {
+        let solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .givens(&[(0, 0), (5, 1)])
+            .build()
+            .unwrap();
+        assert_eq!(solver.state(), &givens(&[(0, 0), (5, 1)]));
+
+        let build = |given| {
+            SolverBuilder::<Storage, STATES, BOARD_SIZE>::new(neighbors, reducer)
+                .givens(&[given])
+                .build()
+                .err()
+        };
+        assert_eq!(
+            build((BOARD_SIZE, 0)),
+            Some(BuildError::Given(BOARD_SIZE, 0))
+        );
+        assert_eq!(build((0, STATES)), Some(BuildError::Given(0, STATES)));
+    }
+
+    #[test]
+    fn border_cells() {
+        let solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .border(1, ROW_LEN)
+            .build()
+            .unwrap();
+        let unknown = (0..BOARD_SIZE)
+            .filter(|&i| solver.state()[i].is_unknown())
+            .collect::<Vec<usize>>();
+        assert_eq!(unknown, [5, 6, 9, 10]);
+        assert_eq!(solver.state()[12], TestCell::reduced(1));
+
+        let build = |row_len| {
+            SolverBuilder::<Storage, STATES, BOARD_SIZE>::new(neighbors, reducer)
+                .state(givens(&[(0, 1), (5, 0), (7, 2)]))
+                .border(1, row_len)
+                .build()
+                .err()
+        };
+        assert_eq!(build(ROW_LEN), Some(BuildError::Border(7)));
+        assert_eq!(build(3), Some(BuildError::RowLength(3)));
+        assert_eq!(build(0), Some(BuildError::RowLength(0)));
+    }
+
+    #[test]
+    /// Weights which rule out every possible state fail rather than backtrack
+    fn solve_zero_weights() {
+        fn only_zero(n: &usize) -> usize {
+            usize::from(*n == 0)
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .weights(only_zero)
+            .build()
+            .unwrap();
+        let actual = solver.solve();
+        assert!(
+            matches!(actual, Err(SolverError::WeightConfig(_))),
+            "Actual: {:?}",
+            actual
+        );
+        assert_eq!(solver.stats().backtracks, 0);
+    }
+
+    #[test]
+    fn validate_zero_weights() {
+        fn skip_two(n: &usize) -> usize {
+            usize::from(*n != 2)
+        }
+
+        let build = |validate| {
+            SolverBuilder::<Storage, STATES, BOARD_SIZE>::new(neighbors, reducer)
+                .weights(skip_two)
+                .validate_weights(validate)
+                .build()
+        };
+        assert_eq!(build(true).err(), Some(BuildError::ZeroWeight(2)));
+        assert!(build(false).is_ok());
+
+        let solver = SolverBuilder::<Storage, STATES, BOARD_SIZE>::new(neighbors, reducer)
+            .validate_weights(true)
+            .build();
+        assert!(solver.is_ok());
+    }
+
+    #[test]
+    /// Cells bordering a strip with no solution are released and re-solved,
+    /// while the rest of the window is kept
+    fn infinite_releases_border() {
+        let mut state = givens(&[(4, 0), (5, 1), (8, 1), (9, 2), (12, 2), (13, 0)]);
+        for cell in state.iter_mut().filter(|cell| cell.is_reduced()) {
+            *cell = cell.collapse();
+        }
+        let solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .state(state)
+            .build()
+            .unwrap();
+        let mut solver = InfiniteSolver::new(solver, ROW_LEN);
+
+        assert_eq!(solver.advance(Pan::Down(1)), Ok(()));
+        assert_eq!(solver.position(), (0, 1));
+        assert!(solver.state().iter().all(|cell| cell.value().is_some()));
+        assert_eq!(solver.solver().check_initial(), Ok(()));
+
+        let kept = [0, 1, 4, 5].map(|i| solver.state()[i].value());
+        assert_eq!(kept, [Some(0), Some(1), Some(1), Some(2)]);
+    }
+
+    #[test]
+    /// Panning past the edge of the window solves a whole new window
+    fn infinite_pans_past_window() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        let mut solver = InfiniteSolver::new(solver, ROW_LEN);
+
+        assert_eq!(solver.advance(Pan::Right(ROW_LEN + 2)), Ok(()));
+        assert_eq!(solver.position(), (ROW_LEN as isize + 2, 0));
+        assert!(solver.state().iter().all(|cell| cell.value().is_some()));
+        assert_eq!(solver.solver().check_initial(), Ok(()));
+    }
+
+    #[test]
+    /// Reducers may describe the states a cell can be in instead
+    fn solve_allow_reducer() {
+        fn allow(neighbors: Vec<(usize, &TestCell)>, i: usize) -> Reduction<Storage> {
+            Reduction::Allow(!reducer(neighbors, i))
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, allow)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_eq!(solver.check_initial(), Ok(()));
+    }
+
+    #[test]
+    /// Reducers which know a cell's value can force it without observing
+    fn solve_force_reducer() {
+        // Each cell is its distance from the top left corner, modulo STATES
+        fn force(neighbors: Vec<(usize, &TestCell)>, i: usize) -> Reduction<Storage> {
+            let distance = |i: usize| i % ROW_LEN + i / ROW_LEN;
+            match neighbors
+                .iter()
+                .find_map(|(j, cell)| cell.value().map(|n| (*j, n)))
+            {
+                Some((j, n)) => Reduction::Force((n + distance(i) + STATES - distance(j)) % STATES),
+                None => Reduction::Deny(State::ZERO),
+            }
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, force)
+            .state(givens(&[(0, 0)]))
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_eq!(solver.stats().observations, 0);
+
+        let actual = solver.state().map(|cell| cell.value().unwrap());
+        let expected = core::array::from_fn(|i| (i % ROW_LEN + i / ROW_LEN) % STATES);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn reset_to_initial() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0)]))
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        solver.reset();
+        assert_eq!(solver.state(), &givens(&[(0, 0)]));
+        assert_eq!(solver.history_depth(), 0);
+    }
+
+    #[test]
+    /// The best scoring solve is kept, here the one with the lowest value in
+    /// its first cell
+    fn solve_best_of_scores() {
+        fn score(state: &SolverState<Storage, STATES, BOARD_SIZE>) -> f64 {
+            -(state[0].value().unwrap() as f64)
+        }
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        let best = solver.solve_best_of(16, score).unwrap();
+        assert_eq!(solver.state(), &best);
+        assert_eq!(best[0].value(), Some(0));
+        assert_eq!(solver.check_initial(), Ok(()));
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve_best_of(2, score), Err(SolverError::Unsolvable));
+    }
+
+    #[test]
+    fn find_seed_within_budget() {
+        let budget = SolverStats {
+            backtracks: 0,
+            ..SolverStats::UNLIMITED
+        };
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer).build().unwrap();
+        let seed = solver.find_good_seed(0..16, budget).unwrap();
+        assert_eq!(solver.unsolved_count(), 0);
+        assert_eq!(solver.stats().backtracks, 0);
+
+        let mut reseeded: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(seed)
+            .build()
+            .unwrap();
+        reseeded.solve().unwrap();
+        assert_eq!(reseeded.state(), solver.state());
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.find_good_seed(0..4, SolverStats::UNLIMITED), None);
+        assert_eq!(solver.state(), &givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]));
+    }
+
+    #[test]
+    fn neighbors_of_cell() {
+        let solver: TestSolver = SolverBuilder::new(neighbors, reducer).build().unwrap();
+        assert_eq!(solver.neighbors_of(5), neighbors(5));
+    }
+
+    #[test]
+    fn assign_values() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer).build().unwrap();
+        assert_eq!(solver.assign(0, 1), Ok(vec![0]));
+        assert_eq!(solver.state()[0].value(), Some(1));
+        assert!(!solver.state()[1].state()[1]);
+        assert_eq!(solver.assign(0, 1), Ok(vec![]));
+        assert_eq!(solver.assign(0, 2), Err(SolverError::Contradiction(0)));
+        assert_eq!(solver.assign(1, 1), Err(SolverError::Contradiction(1)));
+    }
+
+    #[test]
+    /// Assigning reports the cells it forced along with the assigned cell
+    fn assign_reports_forced() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0), (1, 1)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.assign(2, 2), Ok(vec![2, 3]));
+        assert_eq!(solver.state()[3], TestCell::collapsed(3));
+    }
+
+    #[test]
+    /// A failed assignment rolls back every assignment before it
+    fn try_assign_all_rolls_back() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer).build().unwrap();
+        let before = *solver.state();
+        let actual = solver.try_assign_all(&[(0, 0), (5, 1), (3, 0)]);
+        assert_eq!(actual, Err((2, SolverError::Contradiction(3))));
+        assert_eq!(solver.state(), &before);
+
+        assert_eq!(solver.try_assign_all(&[(0, 0), (5, 1)]), Ok(()));
+        assert_eq!(solver.state()[5].value(), Some(1));
+        solver.solve().unwrap();
+        assert_eq!(solver.check_initial(), Ok(()));
+    }
+
+    #[test]
+    fn propagate_to_fixpoint_deduces() {
+        // The last row is determined by the three above it
+        let rows = [0, 1, 2, 3, 1, 2, 3, 0, 2, 3, 0, 1];
+        let state = givens(&rows.iter().copied().enumerate().collect::<Vec<_>>());
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(state)
+            .build()
+            .unwrap();
+        assert_eq!(solver.propagate_to_fixpoint(), Ok(true));
+        let last_row = solver.state()[12..].iter().map(|cell| cell.value());
+        assert!(last_row.eq([3, 0, 1, 2].map(Some)));
+        assert_eq!(solver.history_depth(), 0);
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.propagate_to_fixpoint(), Ok(false));
+        assert_eq!(solver.unsolved_count(), 15);
+
+        // The end of the first row can only be 3, which is already in its column
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0), (1, 1), (2, 2), (7, 3)]))
+            .build()
+            .unwrap();
+        assert_eq!(
+            solver.propagate_to_fixpoint(),
+            Err(SolverError::Contradiction(3))
+        );
+    }
+
+    #[test]
+    fn highest_entropy_heuristic() {
+        for (heuristic, expected) in [
+            (EntropyHeuristic::Lowest, 3),
+            (EntropyHeuristic::Highest, 4),
+        ] {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .state(givens(&[(0, 0)]))
+                .entropy_heuristic(heuristic)
+                .build()
+                .unwrap();
+            solver.propagate_to_fixpoint().unwrap();
+            let i = solver.select_cell().unwrap();
+            assert_eq!(solver.state()[i].entropy(), expected);
+        }
+    }
+
+    #[test]
+    fn named_seeds() {
+        assert_eq!(hash_seed(""), 0xcbf29ce484222325);
+        assert_eq!(hash_seed("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash_seed("foobar"), 0x85944171f73967e8);
+
+        let solve = |seed: &str| {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .seed_str(seed)
+                .build()
+                .unwrap();
+            solver.solve().unwrap();
+            *solver.state()
+        };
+        assert_eq!(solve("forest-42"), solve("forest-42"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn temper_weights() {
+        let mut weights = [1.0, 4.0, 0.0, 4.0];
+        temper(&mut weights, 2.0);
+        assert_eq!(weights, [1.0, 2.0, 0.0, 2.0]);
+
+        temper(&mut weights, 0.0);
+        assert_eq!(weights, [0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    /// At a temperature of 0 the heaviest possible state is always observed
+    fn zero_temperature() {
+        fn heavier(n: &usize) -> usize {
+            n + 1
+        }
+
+        for seed in 0..4 {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .seed(seed)
+                .weights(heavier)
+                .temperature(0.0)
+                .build()
+                .unwrap();
+            solver.solve_n(1).unwrap();
+            let observed = solver.state().iter().filter_map(Cell::value);
+            assert!(observed.eq([STATES - 1]));
+        }
+    }
+
+    #[test]
+    fn branching_factor_mean() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        assert_eq!(solver.stats().branching_factor(), 0.0);
+        solver.solve_n(1).unwrap();
+        assert_eq!(solver.stats().branching_factor(), STATES as f64);
+
+        solver.solve().unwrap();
+        let stats = solver.stats();
+        assert!(stats.observations > 1);
+        let actual = stats.branching_factor();
+        assert!(actual > 1.0 && actual < STATES as f64, "Actual: {}", actual);
+    }
+
+    #[test]
+    fn inert_cell_gap() {
+        let state = givens(&[(0, 0), (1, 1), (2, 2)]);
+        let inert = |reducer: StateReducer<Storage, STATES>, track_inert| {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .state(state)
+                .track_inert(track_inert)
+                .build()
+                .unwrap();
+            solver.propagate_to_fixpoint().unwrap();
+            solver.inert_cells()
+        };
+        let gap: StateReducer<Storage, STATES> = Box::new(|neighbors, i| match i {
+            9 => Reduction::Deny(State::ZERO),
+            _ => reducer(neighbors, i).into(),
+        });
+
+        assert_eq!(inert(Box::new(|n, i| reducer(n, i).into()), true), []);
+        assert_eq!(inert(gap, true), [9]);
+        assert_eq!(inert(Box::new(|n, i| reducer(n, i).into()), false), []);
+    }
+
+    #[test]
+    fn explain_reductions() {
+        let state = givens(&[(0, 0), (1, 1), (2, 2)]);
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(state)
+            .explain(true)
+            .build()
+            .unwrap();
+        solver.propagate_to_fixpoint().unwrap();
+        assert_eq!(solver.state()[3].value(), Some(3));
+
+        let actual = solver.explain(3);
+        let expected = [0, 1, 2].map(|n| (n, State::new(1 << n)));
+        assert_eq!(actual, expected);
+        assert!(solver.explain(0).is_empty());
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(state)
+            .build()
+            .unwrap();
+        solver.propagate_to_fixpoint().unwrap();
+        assert!(solver.explain(3).is_empty());
+    }
+
+    #[test]
+    /// Separate row and column constraints solve like the combined reducer
+    fn solve_with_constraints() {
+        fn line(neighbors: &[(usize, &TestCell)], i: usize, same: fn(usize) -> usize) -> State {
+            neighbors
+                .iter()
+                .filter(|&&(j, _)| same(j) == same(i))
+                .fold(State::ZERO, |acc, (_, cell)| acc | cell.state())
+        }
+
+        let rows = |neighbors: &[(usize, &TestCell)], i| line(neighbors, i, |i| i / ROW_LEN);
+        let columns = |neighbors: &[(usize, &TestCell)], i| line(neighbors, i, |i| i % ROW_LEN);
+        let mut solver: TestSolver =
+            SolverBuilder::constraints(neighbors, vec![Box::new(rows), Box::new(columns)])
+                .state(givens(&[(0, 0)]))
+                .seed(0)
+                .build()
+                .unwrap();
+        solver.solve().unwrap();
+
+        let check: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(*solver.state())
+            .build()
+            .unwrap();
+        assert_eq!(check.check_initial(), Ok(()));
+    }
+
+    #[test]
+    /// Grid reducers see each known neighbor on its side
+    fn grid2d_sides() {
+        // A cell may not share a value with the cell to its left or above
+        fn reducer(neighbors: NeighborsByDir<'_, Storage, STATES>, _: usize) -> State {
+            let mut acc = State::ZERO;
+            for cell in [neighbors.left, neighbors.up].into_iter().flatten() {
+                acc |= cell.state();
+            }
+            acc
+        }
+
+        let mut solver: TestSolver = SolverBuilder::grid2d(ROW_LEN, ROW_LEN, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        assert_eq!(solver.neighbors_of(4), [5, 0, 8]);
+        solver.assign(5, 2).unwrap();
+        assert!(!solver.state()[6].state()[2] && !solver.state()[9].state()[2]);
+        assert!(solver.state()[4].state()[2] && solver.state()[1].state()[2]);
+        solver.solve().unwrap();
+
+        let build = SolverBuilder::<Storage, STATES, BOARD_SIZE>::grid2d(3, 5, reducer).build();
+        assert_eq!(build.err(), Some(BuildError::Dimensions(3, 5)));
+    }
+
+    #[test]
+    /// Cells on the edges constrain the cells on the opposite edge
+    fn grid2d_toroidal() {
+        fn reducer(neighbors: NeighborsByDir<'_, Storage, STATES>, _: usize) -> State {
+            let mut acc = State::ZERO;
+            for cell in [neighbors.left, neighbors.up].into_iter().flatten() {
+                acc |= cell.state();
+            }
+            acc
+        }
+
+        let mut solver: TestSolver = SolverBuilder::grid2d(ROW_LEN, ROW_LEN, reducer)
+            .toroidal()
+            .seed(0)
+            .build()
+            .unwrap();
+        assert_eq!(solver.neighbors_of(0), [3, 1, 12, 4]);
+        solver.assign(3, 1).unwrap();
+        solver.assign(12, 2).unwrap();
+        assert!(!solver.state()[0].state()[1] && !solver.state()[0].state()[2]);
+        solver.solve().unwrap();
+        assert_eq!(solver.unsolved_count(), 0);
+    }
+
+    #[test]
+    /// The reducer is only given the known neighbors, each with its direction
+    fn directed_neighbors() {
+        /// Each cell counts up from the cell to its left
+        fn reducer(neighbors: Vec<(usize, Direction, &TestCell)>, _: usize) -> Reduction<Storage> {
+            let mut allowed = State::ZERO;
+            allowed.fill(true);
+
+            for (_, direction, cell) in neighbors {
+                let n = cell.value().unwrap();
+                let mut next = State::ZERO;
+                match direction {
+                    Direction::Left => next.set((n + 1) % STATES, true),
+                    _ => next.set((n + STATES - 1) % STATES, true),
+                }
+                allowed &= next;
+            }
+
+            Reduction::Allow(allowed)
+        }
+
+        let mut solver: TestSolver = SolverBuilder::directed(row_neighbors, reducer)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_eq!(solver.neighbors_of(4), [5]);
+
+        for (i, cell) in solver.state().iter().enumerate().skip(1) {
+            if i % ROW_LEN > 0 {
+                let left = solver.state()[i - 1].value().unwrap();
+                assert_eq!(cell.value(), Some((left + 1) % STATES));
+            }
+        }
+    }
+
+    #[test]
+    /// Cells following a known cell to their left are always 1, others 0
+    fn directional_weights_bias() {
+        fn flow(n: usize, directions: &[Direction]) -> f64 {
+            let expected = if directions.contains(&Direction::Left) {
+                1
+            } else {
+                0
+            };
+            if n == expected {
+                1.0
+            } else {
+                0.0
+            }
+        }
+
+        fn check(i: usize, n: usize, state: &SolverState<Storage, STATES, BOARD_SIZE>) {
+            let left_known = !i.is_multiple_of(ROW_LEN) && !state[i - 1].is_unknown();
+            assert_eq!(n, left_known as usize, "Cell {}", i);
+        }
+
+        fn unconstrained(_: Vec<(usize, Direction, &TestCell)>, _: usize) -> State {
+            State::ZERO
+        }
+
+        for seed in 0..4 {
+            let mut solver: TestSolver = SolverBuilder::directed(row_neighbors, unconstrained)
+                .seed(seed)
+                .directional_weights(flow)
+                .on_collapse(check)
+                .build()
+                .unwrap();
+            solver.solve().unwrap();
+            assert!(solver.state().iter().all(|cell| cell.value() <= Some(1)));
+        }
+    }
+
+    #[test]
+    /// Each state becomes impossible to observe once it has been placed a
+    /// quarter of the time, so an unconstrained board is evenly split
+    fn dynamic_weights_balance() {
+        struct Balanced;
+
+        impl DynamicWeights<Storage, STATES, BOARD_SIZE> for Balanced {
+            fn weight(
+                &mut self,
+                state: usize,
+                board: &SolverState<Storage, STATES, BOARD_SIZE>,
+            ) -> f64 {
+                let placed = summarize(board).values[state];
+                (BOARD_SIZE / STATES - placed) as f64
+            }
+        }
+
+        for seed in 0..4 {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, |_, _| State::ZERO)
+                .seed(seed)
+                .dynamic_weights(Balanced)
+                .build()
+                .unwrap();
+            solver.solve().unwrap();
+            assert_eq!(
+                summarize(solver.state()).values,
+                [BOARD_SIZE / STATES; STATES]
+            );
+        }
+    }
+
+    #[test]
+    /// Restoring a snapshot replays the same solve, since the rng is restored
+    fn snapshot_restores_rng() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer).build().unwrap();
+        solver.assign(0, 0).unwrap();
+        let snapshot = solver.snapshot();
+        solver.solve().unwrap();
+        let solved = *solver.state();
+
+        solver.restore(snapshot.clone());
+        assert_eq!(solver.state(), snapshot.state());
+        assert_eq!(solver.history_depth(), 0);
+        solver.solve().unwrap();
+        assert_eq!(solver.state(), &solved);
+    }
+
+    #[test]
+    /// A board reloaded with its saved rng solves the same as the original
+    fn resume_with_rng_state() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(3)
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve_budget(2), SolveStatus::InProgress);
+        let saved = (*solver.state(), solver.rng_state());
+        solver.solve().unwrap();
+
+        let mut resumed: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(saved.0)
+            .build()
+            .unwrap();
+        resumed.set_rng_state(saved.1);
+        resumed.solve().unwrap();
+        assert_eq!(resumed.state(), solver.state());
+    }
+
+    #[test]
+    /// Without any history every contradiction clears the cells around it
+    fn solve_with_clear_radius() {
+        let mut clears = 0;
+
+        for seed in 0..24 {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .seed(seed)
+                .max_history(0)
+                .recovery(Recovery::ClearRadius {
+                    radius: 1,
+                    max_clears: 100,
+                })
+                .build()
+                .unwrap();
+            assert_eq!(solver.solve(), Ok(()));
+            assert_eq!(solver.unsolved_count(), 0);
+            assert_eq!(solver.check_initial(), Ok(()));
+            clears += solver.stats().clears;
+        }
+
+        assert!(clears > 0);
+    }
+
+    #[test]
+    /// Clearing never touches the givens, so an unsolvable board still fails
+    fn clear_radius_keeps_givens() {
+        let board = givens(&[(9, 0), (14, 0), (4, 0), (3, 1)]);
+
+        for seed in 0..8 {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .state(board)
+                .seed(seed)
+                .max_history(0)
+                .recovery(Recovery::ClearRadius {
+                    radius: 1,
+                    max_clears: 5,
+                })
+                .build()
+                .unwrap();
+            assert_eq!(solver.solve(), Err(SolverError::Unsolvable));
+            assert!(solver.stats().clears <= 5);
+            for (i, n) in [(9, 0), (14, 0), (4, 0), (3, 1)] {
+                assert_eq!(solver.state()[i].value(), Some(n));
+            }
+        }
+    }
+
+    #[test]
+    /// Conflicting givens are collapsed to the fallback rather than failing
+    fn solve_best_effort() {
+        let mut fallbacks = 0;
+
+        for seed in 0..8 {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .state(givens(&[(0, 1), (1, 1)]))
+                .seed(seed)
+                .recovery(Recovery::BestEffort(0))
+                .build()
+                .unwrap();
+            assert_eq!(solver.solve(), Ok(()));
+            assert_eq!(solver.unsolved_count(), 0);
+            assert_eq!(solver.stats().backtracks, 0);
+            fallbacks += solver.stats().fallbacks;
+        }
+
+        assert!(fallbacks > 0);
+        assert_eq!(
+            SolverBuilder::<Storage, STATES, BOARD_SIZE>::new(neighbors, reducer)
+                .recovery(Recovery::BestEffort(STATES))
+                .build()
+                .err(),
+            Some(BuildError::Fallback(STATES))
+        );
+    }
+
+    #[test]
+    fn solve_with_backjumps() {
+        for seed in 0..8 {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .seed(seed)
+                .recovery(Recovery::Backjump)
+                .build()
+                .unwrap();
+            assert_eq!(solver.solve(), Ok(()));
+            assert_eq!(solver.check_initial(), Ok(()));
+        }
+    }
+
+    #[test]
+    /// Backjumping skips the observations a conflict wasn't derived from
+    fn backjump_to_culprit() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .recovery(Recovery::Backjump)
+            .build()
+            .unwrap();
+        for i in [0, 10, 15] {
+            let to_collapse = solver.observe(i).unwrap();
+            solver.propagate(to_collapse).unwrap();
+        }
+
+        // Cell 1 was only reduced by the observation of cell 0
+        solver.backjump(1);
+        assert_eq!(solver.history_depth(), 1);
+        assert_eq!(solver.stats().backjumps, 2);
+
+        // Cell 5 wasn't derived from any observation, so nothing is skipped
+        solver.backjump(5);
+        assert_eq!(solver.history_depth(), 1);
+    }
+
+    #[test]
+    /// Observations reducing a cell through its neighbors are never skipped
+    fn backjump_through_chain() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .recovery(Recovery::Backjump)
+            .build()
+            .unwrap();
+        for i in [0, 10, 14, 6, 12] {
+            let to_collapse = solver.observe(i).unwrap();
+            solver.propagate(to_collapse).unwrap();
+        }
+
+        // Cell 2 was reduced by the observations in its column, which cell 1
+        // is then derived from even though they aren't its neighbors, so the
+        // observation of cell 0 isn't the next undone
+        assert!(solver.state()[2].is_collapsed());
+        solver.backjump(1);
+        assert!(solver.history_depth() >= 3);
+        assert_eq!(solver.stats().backjumps, 5 - solver.history_depth());
+    }
+
+    #[test]
+    /// Backjumping finds a solution whenever backtracking does
+    fn backjump_is_complete() {
+        for k in 0..64 {
+            let board = givens(&[
+                (k % BOARD_SIZE, k % STATES),
+                ((k * 7 + 3) % BOARD_SIZE, k / 4 % STATES),
+                ((k * 5 + 9) % BOARD_SIZE, k / 16 % STATES),
+            ]);
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .state(board)
+                .build()
+                .unwrap();
+            let solvable = solver.solutions().next().is_some();
+
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .state(board)
+                .seed(k as u64)
+                .recovery(Recovery::Backjump)
+                .build()
+                .unwrap();
+            assert_eq!(solver.solve().is_ok(), solvable, "Board {}", k);
+        }
+    }
+
+    #[test]
+    fn solve_partially() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        let collapsed = solver.solve_n(2).unwrap();
+        assert!(collapsed >= 2);
+        assert_eq!(collapsed, BOARD_SIZE - solver.unsolved_count());
+        assert!(solver.state().iter().any(Cell::is_unknown));
+        assert_eq!(solver.check_initial(), Ok(()));
+
+        solver.solve().unwrap();
+        assert_eq!(solver.solve_n(2), Ok(0));
+    }
+
+    #[test]
+    fn solve_with_restarts() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(12)
+            .recovery(Recovery::Restart(100))
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Ok(()));
+        assert_eq!(solver.check_initial(), Ok(()));
+        assert_eq!(solver.stats().backtracks, 0);
+        assert!(solver.stats().restarts > 0);
+        assert_eq!(solver.history_depth(), 0);
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]))
+            .recovery(Recovery::Restart(100))
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Err(SolverError::Unsolvable));
+    }
+
+    #[test]
+    /// The only cell left for the last 3 is already 0, which is only found
+    /// after observing, so each restart fails the same way until the limit
+    fn restart_limit() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(3, 3), (5, 0), (10, 3), (12, 3)]))
+            .seed(0)
+            .recovery(Recovery::Restart(5))
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve(), Err(SolverError::Unsolvable));
+        assert_eq!(solver.stats().restarts, 5);
+    }
+
+    #[test]
+    fn forbid_values() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.forbid(1, 0), Ok(()));
+        assert_eq!(solver.forbid(1, 1), Ok(()));
+        assert!(solver.state()[1].is_unknown());
+        assert_eq!(solver.forbid(1, 2), Ok(()));
+        assert_eq!(solver.state()[1].value(), Some(3));
+        assert!(solver.state()[5].is_unknown());
+        assert!(!solver.state()[5].state()[3]);
+
+        assert_eq!(solver.forbid(0, 0), Err(SolverError::Contradiction(0)));
+        assert_eq!(solver.forbid(1, 3), Err(SolverError::Contradiction(1)));
+
+        // Values past the states are rejected, whether or not the storage has
+        // room for them
+        let state = *solver.state();
+        for value in [STATES, Storage::BITS as usize] {
+            assert_eq!(
+                solver.forbid(2, value),
+                Err(SolverError::Build(BuildError::Given(2, value)))
+            );
+        }
+        assert_eq!(solver.state(), &state);
+    }
+
+    #[test]
+    /// Forcing the second row's first cell to 2 leaves 1 for both of the
+    /// cells after the 0, which fails without touching the history
+    fn forbid_rolls_back() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(3, 3), (5, 0), (10, 3), (12, 3)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.solve_budget(0), SolveStatus::InProgress);
+        let state = *solver.state();
+        assert_eq!(solver.forbid(4, 1), Err(SolverError::Contradiction(7)));
+        assert_eq!(solver.state(), &state);
+        assert_eq!(solver.history_depth(), 1);
+    }
+
+    #[test]
+    /// A 4x4 latin square has 576 distinct solutions
+    fn enumerate_solutions() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        let solutions = solver
+            .solutions()
+            .map(|state| state.map(|cell| cell.value().unwrap()))
+            .collect::<Vec<_>>();
+        let distinct = solutions.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(solutions.len(), 576);
+        assert_eq!(distinct.len(), 576);
+    }
+
+    #[test]
+    /// Earlier observations are kept rather than backtracked into
+    fn enumerate_after_observing() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve_n(1).unwrap();
+        let known = (0..BOARD_SIZE)
+            .filter_map(|i| solver.state()[i].value().map(|n| (i, n)))
+            .collect::<Vec<_>>();
+        let solutions = solver.solutions().collect::<Vec<_>>();
+        assert_eq!(solutions.len(), 576 / STATES);
+        assert!(solutions
+            .iter()
+            .all(|state| known.iter().all(|&(i, n)| state[i].value() == Some(n))));
+
+        solver.reset();
+        assert_eq!(solver.solutions().count(), 576);
+    }
+
+    #[test]
+    fn unique_solution() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[
+                (0, 0),
+                (1, 1),
+                (2, 2),
+                (4, 1),
+                (5, 2),
+                (6, 3),
+                (8, 2),
+                (9, 3),
+                (10, 0),
+            ]))
+            .recovery(Recovery::Restart(100))
+            .build()
+            .unwrap();
+        assert_eq!(solver.solutions().take(2).count(), 1);
+        assert_eq!(solver.recovery, Recovery::Restart(100));
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]))
+            .build()
+            .unwrap();
+        assert!(solver.solutions().next().is_none());
+    }
+
+    #[test]
+    fn check_initial_valid() {
+        let solver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0), (5, 1), (10, 2)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.check_initial(), Ok(()));
+    }
+
+    #[test]
+    /// Two of the same value in a row conflict with each other
+    fn check_initial_duplicate_in_row() {
+        let solver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 3), (2, 3)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.check_initial(), Err(vec![0, 2]));
+    }
+
+    #[test]
+    /// An unknown cell whose row and column cover every value cannot be filled
+    fn check_initial_exhausted_cell() {
+        let solver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(1, 0), (2, 1), (4, 2), (8, 3)]))
+            .build()
+            .unwrap();
+        assert_eq!(solver.check_initial(), Err(vec![0]));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    /// A solved board can be read concurrently through shared references
+    fn shared_readers() {
+        fn assert_sync<T: Sync>(_: &T) {}
+
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_sync(&solver);
+
+        let expected = *solver.state();
+        let solver = &solver;
+        std::thread::scope(|scope| {
+            let readers = (0..4)
+                .map(|_| scope.spawn(move || (*solver.state(), solver.entropy_map())))
+                .collect::<Vec<_>>();
+
+            for reader in readers {
+                let (state, entropy) = reader.join().unwrap();
+                assert_eq!(state, expected);
+                assert!(entropy.iter().all(|&entropy| entropy == 0));
+            }
+        });
+    }
+
+    #[test]
+    fn preferred_states() {
+        for seed in 0..8 {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .seed(seed)
+                .build()
+                .unwrap();
+            solver.prefer(0, 2, 1e9);
+            solver.prefer(5, 3, 0.0);
+            solver.observe(0).unwrap();
+            assert_eq!(solver.state()[0].value(), Some(2));
+            solver.observe(5).unwrap();
+            assert_ne!(solver.state()[5].value(), Some(3));
+        }
+
+        // A preference for a state which was ruled out is ignored
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(1, 2)]))
+            .build()
+            .unwrap();
+        solver.prefer(0, 2, 1e9);
+        solver.solve().unwrap();
+        assert_ne!(solver.state()[0].value(), Some(2));
+    }
+
+    #[test]
+    /// An L-shaped board, with the top right quarter carved out
+    fn void_cells() {
+        let carved = [2, 3, 6, 7];
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .void(&carved)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        assert_eq!(solver.unsolved_count(), 0);
+        assert_eq!(solver.check_initial(), Ok(()));
+        for i in 0..BOARD_SIZE {
+            assert_eq!(solver.is_void(i), carved.contains(&i));
+            assert_eq!(solver.state()[i].is_unknown(), carved.contains(&i));
+        }
+
+        let build = SolverBuilder::<Storage, STATES, BOARD_SIZE>::new(neighbors, reducer)
+            .void(&[BOARD_SIZE])
+            .build();
+        assert_eq!(build.err(), Some(BuildError::Void(BOARD_SIZE)));
+    }
+
+    #[test]
+    /// Ties go to the lowest index without drawing from the rng
+    fn deterministic_ties() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .deterministic()
+            .build()
+            .unwrap();
+        let rng = solver.rng_state();
+        assert_eq!(solver.select_cell(), Some(0));
+        solver.assign(0, 0).unwrap();
+        assert_eq!(solver.select_cell(), Some(1));
+        assert_eq!(solver.rng_state(), rng);
+
+        let solve = || {
+            let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                .deterministic()
+                .build()
+                .unwrap();
+            solver.solve().unwrap();
+            *solver.state()
+        };
+        assert_eq!(solve(), solve());
+    }
+
+    #[test]
+    fn edit_given_clears_conflicts() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0), (1, 1)]))
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        let conflicts = (0..BOARD_SIZE)
+            .filter(|&j| neighbors(1).contains(&j) && solver.state()[j].value() == Some(2))
+            .collect::<Vec<_>>();
+
+        let cleared = solver.edit_given(1, Some(2)).unwrap();
+        assert!(conflicts.iter().all(|j| cleared.contains(j)));
+        assert_eq!(solver.state()[1].value(), Some(2));
+        assert_eq!(solver.check_initial(), Ok(()));
+        solver.solve().unwrap();
+        assert_eq!(solver.check_initial(), Ok(()));
+
+        solver.edit_given(0, None).unwrap();
+        solver.reset();
+        assert!(solver.state()[0].is_unknown());
+        assert_eq!(solver.state()[1], TestCell::reduced(2));
+    }
+
+    #[test]
+    /// A removed given is reduced against the solved cells around it, so
+    /// solving again can't give it a conflicting value
+    fn edit_given_removes_from_solved_board() {
+        for seed in 0..8 {
+            for i in 0..BOARD_SIZE {
+                let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+                    .state(givens(&[(i, 0)]))
+                    .seed(seed)
+                    .build()
+                    .unwrap();
+                solver.solve().unwrap();
+
+                solver.edit_given(i, None).unwrap();
+                assert_eq!(solver.solve(), Ok(()));
+                assert_eq!(solver.verify(), Ok(()), "Seed {}, cell {}", seed, i);
+            }
+        }
+    }
+
+    #[test]
+    /// A failed edit leaves the board, givens, and history as they were
+    fn edit_given_rolls_back() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .state(givens(&[(0, 0), (1, 1), (2, 2)]))
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        let before = *solver.state();
+        let depth = solver.history_depth();
+
+        assert_eq!(
+            solver.edit_given(1, Some(STATES)),
+            Err(SolverError::Build(BuildError::Given(1, STATES)))
+        );
+        // Cell 3 can only be 3
+        assert_eq!(
+            solver.edit_given(7, Some(3)),
+            Err(SolverError::Contradiction(3))
+        );
+        assert_eq!(*solver.state(), before);
+        assert_eq!(solver.history_depth(), depth);
+
+        solver.reset();
+        assert_eq!(solver.state()[2], TestCell::reduced(2));
+        assert!(solver.state()[7].is_unknown());
+    }
+
+    #[test]
+    fn frozen_cells() {
+        let mut solver: TestSolver = SolverBuilder::new(neighbors, reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.assign(5, 1).unwrap();
+        solver.freeze(&[0, 5]);
+        solver.solve().unwrap();
+        assert_eq!(solver.unsolved_count(), 0);
+        assert_eq!(solver.state()[0], TestCell::default());
+        assert_eq!(solver.state()[5].value(), Some(1));
+        assert_eq!(solver.check_initial(), Ok(()));
+
+        let state = *solver.state();
+        solver.pan_xy(1, 0, ROW_LEN);
+        assert_eq!(solver.state()[0], TestCell::default());
+        assert_eq!(solver.state()[4], TestCell::default());
+        assert_eq!(solver.state()[5], state[5]);
+        assert_eq!(solver.state()[6], state[7]);
+    }
+
+    #[test]
+    fn summarize_cells() {
+        let mut state = givens(&[(0, 1), (5, 1), (10, 3)]);
+        state[0] = state[0].collapse();
+        let expected = StateSummary {
+            unknown: 13,
+            reduced: 2,
+            collapsed: 1,
+            values: [0, 2, 0, 1],
+        };
+        assert_eq!(summarize(&state), expected);
+    }
+
+    #[test]
+    /// Propagation reaches every cell a reducer constrains, not just adjacent
+    /// ones, when neighbors span whole rows, columns and boxes
+    fn non_local_neighbors() {
+        type SudokuCell = Cell<u16, 9>;
+
+        fn sudoku_neighbors(i: usize) -> Vec<usize> {
+            let (x, y) = (i % 9, i / 9);
+            let head = y / 3 * 27 + x / 3 * 3;
+            let mut neighbors = (0..9)
+                .map(|j| y * 9 + j)
+                .chain((0..9).map(|j| j * 9 + x))
+                .chain((0..9).map(|j| head + j / 3 * 9 + j % 3))
+                .filter(|&j| j != i)
+                .collect::<Vec<_>>();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            neighbors
+        }
+
+        fn sudoku_reducer(neighbors: Vec<(usize, &SudokuCell)>, _: usize) -> BitArray<u16> {
+            neighbors
+                .into_iter()
+                .fold(BitArray::ZERO, |acc, (_, cell)| acc | cell.state())
+        }
+
+        assert_eq!(sudoku_neighbors(40).len(), 20);
+
+        let mut solver = SolverBuilder::<u16, 9, 81>::new(sudoku_neighbors, sudoku_reducer)
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.assign(40, 4).unwrap();
+        for j in sudoku_neighbors(40) {
+            assert!(!solver.state()[j].state()[4], "Cell {} can still be 4", j);
+        }
+
+        solver.solve().unwrap();
+        for i in 0..81 {
+            let n = solver.state()[i].value().unwrap();
+            assert!(sudoku_neighbors(i)
+                .into_iter()
+                .all(|j| solver.state()[j].value() != Some(n)));
+        }
+    }
+
+    #[test]
+    /// Solvers aren't limited to the states which fit in an integer
+    fn solve_many_states() {
+        type WideCell = Cell<[u64; 5], 300>;
+
+        fn row(i: usize) -> Vec<usize> {
+            (0..8).filter(|&j| j != i).collect()
+        }
+
+        fn distinct(neighbors: Vec<(usize, &WideCell)>, _: usize) -> CellState<[u64; 5]> {
+            neighbors
+                .into_iter()
+                .fold(BitArray::ZERO, |acc, (_, cell)| acc | cell.state())
+        }
+
+        let mut solver = SolverBuilder::<[u64; 5], 300, 8>::new(row, distinct)
+            .givens(&[(0, 299)])
+            .seed(0)
+            .build()
+            .unwrap();
+        solver.solve().unwrap();
+        let mut values = solver.state().map(|cell| cell.value().unwrap());
+        assert_eq!(values[0], 299);
+        values.sort();
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
     }
 }
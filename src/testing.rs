@@ -0,0 +1,98 @@
+use alloc::vec::Vec;
+use bitvec::view::BitViewSized;
+use core::fmt::Debug;
+
+use crate::{
+    cell::{Cell, Reduction},
+    solver::{MaybeSync, Neighbors, SolverBuilder, SolverError, SolverState},
+};
+
+/// The number of seeds each sample is solved with by `assert_reducer_sound`
+pub const SEEDS_PER_SAMPLE: u64 = 16;
+
+/// Solves each sample, which must be known to be solvable, with seeds
+/// `0..SEEDS_PER_SAMPLE` and panics if the reducer ever makes a sample
+/// unsolvable or produces a board it would itself reject. The panic names
+/// the sample and seed so the failure can be reproduced
+/// ```
+/// use wave_function_collapse::{puzzles::Sudoku, testing::assert_reducer_sound};
+///
+/// type Puzzle = Sudoku<2, 2>;
+///
+/// assert_reducer_sound::<u8, { Puzzle::SIDE }, { Puzzle::CELLS }, _>(
+///     Puzzle::neighbors,
+///     Puzzle::reducer,
+///     &[Default::default()],
+/// );
+/// ```
+pub fn assert_reducer_sound<
+    A: BitViewSized + Copy + Debug + MaybeSync + 'static,
+    const N: usize,
+    const S: usize,
+    T: Into<Reduction<A>>,
+>(
+    neighbors: Neighbors,
+    reducer: impl Fn(Vec<(usize, &Cell<A, N>)>, usize) -> T + Clone + Send + Sync + 'static,
+    samples: &[SolverState<A, N, S>],
+) {
+    for (sample, state) in samples.iter().enumerate() {
+        for seed in 0..SEEDS_PER_SAMPLE {
+            let mut solver = SolverBuilder::new(neighbors, reducer.clone())
+                .state(*state)
+                .seed(seed)
+                .build()
+                .unwrap_or_else(|e| panic!("Sample {} failed to build: {}", sample, e));
+
+            match solver.solve() {
+                Ok(()) => {}
+                Err(SolverError::Unsolvable) => {
+                    panic!("Sample {} was unsolvable with seed {}", sample, seed)
+                }
+                Err(e) => panic!("Sample {} failed with seed {}: {}", sample, seed, e),
+            }
+
+            if let Err(cells) = solver.check_initial() {
+                panic!(
+                    "Sample {} solved with seed {} has rejected cells {:?}",
+                    sample, seed, cells
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cell::CellState, puzzles::Sudoku};
+
+    type Puzzle = Sudoku<2, 2>;
+    type PuzzleCell = Cell<u8, { Puzzle::SIDE }>;
+
+    #[test]
+    fn sound_reducer() {
+        assert_reducer_sound::<u8, { Puzzle::SIDE }, { Puzzle::CELLS }, _>(
+            Puzzle::neighbors,
+            Puzzle::reducer,
+            &[[Cell::default(); Puzzle::CELLS]],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "with seed 0")]
+    /// A reducer which forbids the last state everywhere can't fill a board
+    /// needing every state
+    fn unsound_reducer() {
+        fn forbid_last(neighbors: Vec<(usize, &PuzzleCell)>, i: usize) -> CellState<u8> {
+            let mut acc = Puzzle::reducer(neighbors, i);
+            acc.set(Puzzle::SIDE - 1, true);
+            acc
+        }
+
+        assert_reducer_sound::<u8, { Puzzle::SIDE }, { Puzzle::CELLS }, _>(
+            Puzzle::neighbors,
+            forbid_last,
+            &[[Cell::default(); Puzzle::CELLS]],
+        );
+    }
+}
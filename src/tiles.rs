@@ -0,0 +1,535 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use bitvec::{array::BitArray, view::BitViewSized};
+use core::{fmt::Debug, ops::BitOr};
+
+use crate::{
+    cell::{Cell, CellState, Reduction},
+    grid::{DirectedReducer, Direction},
+    solver::StateReducer,
+};
+
+/// The sides of a tile which connect to the neighboring tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sides(u8);
+
+impl Sides {
+    pub const NONE: Self = Self(0);
+    pub const LEFT: Self = Self(0b0001);
+    pub const RIGHT: Self = Self(0b0010);
+    pub const TOP: Self = Self(0b0100);
+    pub const BOTTOM: Self = Self(0b1000);
+    pub const ALL: Self = Self(0b1111);
+
+    /// Combines the connections of both sides, usable in constants
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Whether every side in `other` connects
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Rotates the tile a quarter turn clockwise, so a connection on the left
+    /// moves to the top
+    pub const fn rotate(self) -> Self {
+        let mut rotated = Self::NONE;
+
+        if self.contains(Self::LEFT) {
+            rotated = rotated.union(Self::TOP);
+        }
+
+        if self.contains(Self::TOP) {
+            rotated = rotated.union(Self::RIGHT);
+        }
+
+        if self.contains(Self::RIGHT) {
+            rotated = rotated.union(Self::BOTTOM);
+        }
+
+        if self.contains(Self::BOTTOM) {
+            rotated = rotated.union(Self::LEFT);
+        }
+
+        rotated
+    }
+
+    /// The tile followed by its three clockwise rotations
+    pub const fn rotations(self) -> [Self; 4] {
+        let once = self.rotate();
+        let twice = once.rotate();
+        [self, once, twice, twice.rotate()]
+    }
+}
+
+impl BitOr for Sides {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+/// Expands each base tile into its distinct rotations, in order
+/// ```
+/// use wave_function_collapse::tiles::{expand, Sides};
+///
+/// let line = Sides::LEFT | Sides::RIGHT;
+/// assert_eq!(expand(&[line]), [line, Sides::TOP | Sides::BOTTOM]);
+/// ```
+pub fn expand(bases: &[Sides]) -> Vec<Sides> {
+    let mut tiles = vec![];
+
+    for rotation in bases.iter().flat_map(|base| base.rotations()) {
+        if !tiles.contains(&rotation) {
+            tiles.push(rotation);
+        }
+    }
+
+    tiles
+}
+
+/// The states a cell cannot be in given the tile of its neighbor in each
+/// direction. `left[t]` is the deny-mask for a cell whose left neighbor is
+/// the tth tile, and so on for each side
+#[derive(Debug, Clone)]
+pub struct TileRules<A: BitViewSized> {
+    pub left: Vec<CellState<A>>,
+    pub right: Vec<CellState<A>>,
+    pub top: Vec<CellState<A>>,
+    pub bottom: Vec<CellState<A>>,
+}
+
+impl<A: BitViewSized + Copy> TileRules<A> {
+    /// Builds the rules for a set of tiles where connected sides must meet
+    /// connected sides and disconnected sides must meet disconnected sides
+    pub fn new(tiles: &[Sides]) -> Self {
+        let masks = |side: Sides, opposite: Sides| {
+            tiles
+                .iter()
+                .map(|neighbor| {
+                    let mut bits = BitArray::ZERO;
+                    for (n, tile) in tiles.iter().enumerate() {
+                        bits.set(n, tile.contains(side) != neighbor.contains(opposite));
+                    }
+                    bits
+                })
+                .collect::<Vec<CellState<A>>>()
+        };
+
+        Self {
+            left: masks(Sides::LEFT, Sides::RIGHT),
+            right: masks(Sides::RIGHT, Sides::LEFT),
+            top: masks(Sides::TOP, Sides::BOTTOM),
+            bottom: masks(Sides::BOTTOM, Sides::TOP),
+        }
+    }
+
+    /// The deny-masks for a cell whose neighbor lies in `direction`
+    pub fn masks(&self, direction: Direction) -> &[CellState<A>] {
+        match direction {
+            Direction::Left => &self.left,
+            Direction::Right => &self.right,
+            Direction::Up => &self.top,
+            Direction::Down => &self.bottom,
+        }
+    }
+}
+
+impl<A: BitViewSized + Copy + Debug + Send + Sync + 'static> TileRules<A> {
+    /// Converts the rules into a reducer for a grid `row_len` cells wide. Each
+    /// known neighbor directly left, right, above, or below a cell denies the
+    /// states in its mask for that direction, any other neighbor is ignored
+    pub fn into_reducer<const N: usize>(self, row_len: usize) -> StateReducer<A, N> {
+        Box::new(move |neighbors: Vec<(usize, &Cell<A, N>)>, i: usize| {
+            let mut acc = BitArray::ZERO;
+
+            for (j, cell) in neighbors {
+                if let (Some(tile), Some(direction)) =
+                    (cell.value(), Direction::between(i, j, row_len))
+                {
+                    acc |= self.masks(direction)[tile];
+                }
+            }
+
+            Reduction::Deny(acc)
+        })
+    }
+
+    /// Converts the rules into a reducer for `SolverBuilder::directed`, which
+    /// is given the direction of each neighbor so it works on any 4-connected
+    /// grid without knowing its width
+    pub fn into_directed_reducer<const N: usize>(self) -> DirectedReducer<A, N> {
+        Box::new(
+            move |neighbors: Vec<(usize, Direction, &Cell<A, N>)>, _: usize| {
+                let mut acc = BitArray::ZERO;
+
+                for (_, direction, cell) in neighbors {
+                    if let Some(tile) = cell.value() {
+                        acc |= self.masks(direction)[tile];
+                    }
+                }
+
+                Reduction::Deny(acc)
+            },
+        )
+    }
+}
+
+/// The deny-masks for a fixed number of tiles, laid out like `TileRules` but
+/// in arrays so they can be built at compile time with `tile_rules!`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileMasks<A: BitViewSized, const T: usize> {
+    pub left: [CellState<A>; T],
+    pub right: [CellState<A>; T],
+    pub top: [CellState<A>; T],
+    pub bottom: [CellState<A>; T],
+}
+
+impl<A: BitViewSized + Copy, const T: usize> TileMasks<A, T> {
+    /// The deny-masks for a cell whose neighbor lies in `direction`
+    pub fn masks(&self, direction: Direction) -> &[CellState<A>] {
+        match direction {
+            Direction::Left => &self.left,
+            Direction::Right => &self.right,
+            Direction::Up => &self.top,
+            Direction::Down => &self.bottom,
+        }
+    }
+
+    /// Copies the masks into `TileRules` to build a reducer from them
+    pub fn to_rules(&self) -> TileRules<A> {
+        TileRules {
+            left: self.left.to_vec(),
+            right: self.right.to_vec(),
+            top: self.top.to_vec(),
+            bottom: self.bottom.to_vec(),
+        }
+    }
+}
+
+/// The deny-masks of `tiles` for a cell whose neighbor lies on `side`, each
+/// packed into the low bits of a `u128`, see `TileRules::new`
+#[doc(hidden)]
+pub const fn side_masks<const T: usize>(
+    tiles: &[Sides; T],
+    side: Sides,
+    opposite: Sides,
+) -> [u128; T] {
+    let mut masks = [0; T];
+    let mut neighbor = 0;
+
+    while neighbor < T {
+        let mut n = 0;
+
+        while n < T {
+            if tiles[n].contains(side) != tiles[neighbor].contains(opposite) {
+                masks[neighbor] |= 1 << n;
+            }
+            n += 1;
+        }
+
+        neighbor += 1;
+    }
+
+    masks
+}
+
+/// Declares a constant `TileMasks` from the connected sides of each tile,
+/// built at compile time with the same rules as `TileRules::new`. The storage
+/// type must be a primitive unsigned integer with a bit for every tile
+/// ```
+/// use wave_function_collapse::{tile_rules, tiles::Sides};
+///
+/// tile_rules! {
+///     /// A horizontal line, a vertical line, and a blank tile
+///     const LINES: u8 = [
+///         Sides::LEFT.union(Sides::RIGHT),
+///         Sides::TOP.union(Sides::BOTTOM),
+///         Sides::NONE,
+///     ];
+/// }
+///
+/// // A line to the left must continue into the cell
+/// assert_eq!(LINES.left[0].data, 0b110);
+/// let reducer = LINES.to_rules().into_directed_reducer::<3>();
+/// ```
+#[macro_export]
+macro_rules! tile_rules {
+    ($(#[$meta:meta])* $vis:vis const $name:ident: $storage:ty = [$($tile:expr),* $(,)?];) => {
+        $(#[$meta])*
+        $vis const $name: $crate::tiles::TileMasks<$storage, { [$(stringify!($tile)),*].len() }> = {
+            use $crate::{cell::CellState, tiles::{side_masks, Sides, TileMasks}};
+
+            const TILES: [Sides; [$(stringify!($tile)),*].len()] = [$($tile),*];
+            assert!(
+                TILES.len() <= <$storage>::BITS as usize,
+                "There are more tiles than bits in the storage type"
+            );
+
+            let bits = [
+                side_masks(&TILES, Sides::LEFT, Sides::RIGHT),
+                side_masks(&TILES, Sides::RIGHT, Sides::LEFT),
+                side_masks(&TILES, Sides::TOP, Sides::BOTTOM),
+                side_masks(&TILES, Sides::BOTTOM, Sides::TOP),
+            ];
+            let mut masks = [[CellState::<$storage>::ZERO; TILES.len()]; 4];
+            let mut side = 0;
+
+            while side < masks.len() {
+                let mut t = 0;
+
+                while t < TILES.len() {
+                    masks[side][t] = CellState {
+                        _ord: ::core::marker::PhantomData,
+                        data: bits[side][t] as $storage,
+                    };
+                    t += 1;
+                }
+
+                side += 1;
+            }
+
+            let [left, right, top, bottom] = masks;
+            TileMasks { left, right, top, bottom }
+        };
+    };
+}
+
+/// A reusable set of tiles, each drawn as a glyph, along with which tiles may
+/// sit next to each other. Implementing it once gives the rules for a reducer
+/// and conversions between cells and glyphs for parsing and rendering
+pub trait TileSet {
+    /// The number of tiles in the set
+    const N: usize;
+
+    /// The character the tile is drawn as
+    fn glyph(tile: usize) -> char;
+
+    /// Whether `tile` may be placed with `neighbor` lying in `direction`
+    fn allowed(tile: usize, neighbor: usize, direction: Direction) -> bool;
+
+    /// The tile drawn as `glyph`, if any
+    fn tile(glyph: char) -> Option<usize> {
+        (0..Self::N).find(|&tile| Self::glyph(tile) == glyph)
+    }
+
+    /// The deny-masks for each direction built from `allowed`.
+    ///
+    /// Panics if there are more tiles than bits in `A`
+    fn rules<A: BitViewSized + Copy>() -> TileRules<A> {
+        let masks = |direction: Direction| {
+            (0..Self::N)
+                .map(|neighbor| {
+                    let mut bits = BitArray::ZERO;
+                    for tile in 0..Self::N {
+                        bits.set(tile, !Self::allowed(tile, neighbor, direction));
+                    }
+                    bits
+                })
+                .collect::<Vec<CellState<A>>>()
+        };
+
+        TileRules {
+            left: masks(Direction::Left),
+            right: masks(Direction::Right),
+            top: masks(Direction::Up),
+            bottom: masks(Direction::Down),
+        }
+    }
+
+    /// Parses a glyph as a reduced cell, or `unknown` as an unknown cell, for
+    /// use with `Board::from_chars`
+    fn parse_cell<A: BitViewSized + Copy + Debug, const N: usize>(
+        glyph: char,
+        unknown: char,
+    ) -> Option<Cell<A, N>> {
+        if glyph == unknown {
+            Some(Cell::default())
+        } else {
+            Self::tile(glyph)
+                .filter(|&tile| tile < N)
+                .map(Cell::reduced)
+        }
+    }
+
+    /// The glyph of a known cell, or `unknown` for any other cell
+    fn render_cell<A: BitViewSized + Copy + Debug, const N: usize>(
+        cell: &Cell<A, N>,
+        unknown: char,
+    ) -> char {
+        cell.value().map_or(unknown, Self::glyph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORNER: Sides = Sides::LEFT.union(Sides::BOTTOM);
+    const TEE: Sides = Sides::LEFT.union(Sides::RIGHT).union(Sides::TOP);
+    const LINE: Sides = Sides::LEFT.union(Sides::RIGHT);
+
+    #[test]
+    fn rotate_corner() {
+        assert_eq!(
+            CORNER.rotations(),
+            [
+                Sides::LEFT | Sides::BOTTOM,
+                Sides::TOP | Sides::LEFT,
+                Sides::RIGHT | Sides::TOP,
+                Sides::BOTTOM | Sides::RIGHT,
+            ]
+        );
+    }
+
+    #[test]
+    /// Symmetric tiles only produce their distinct rotations
+    fn expand_pipes() {
+        let tiles = expand(&[CORNER, TEE, LINE, Sides::ALL, Sides::NONE]);
+        assert_eq!(tiles.len(), 12);
+        assert_eq!(expand(&[Sides::ALL]), [Sides::ALL]);
+    }
+
+    #[test]
+    /// The generated rules match the pipe tables once written by hand
+    fn rules_match_pipes() {
+        let tiles = [
+            CORNER,
+            Sides::TOP | Sides::RIGHT,
+            TEE,
+            Sides::LEFT | Sides::RIGHT | Sides::BOTTOM,
+            Sides::TOP | Sides::BOTTOM | Sides::RIGHT,
+            LINE,
+            Sides::ALL,
+            Sides::TOP | Sides::BOTTOM,
+            Sides::TOP | Sides::BOTTOM | Sides::LEFT,
+            Sides::TOP | Sides::LEFT,
+            Sides::BOTTOM | Sides::RIGHT,
+            Sides::NONE,
+        ];
+        let rules = TileRules::<u16>::new(&tiles);
+        let to_u16 = |masks: &[CellState<u16>]| masks.iter().map(|m| m.data).collect::<Vec<_>>();
+
+        let (connected, disconnected) = (0b0011_0110_1101, 0b1100_1001_0010);
+        assert_eq!(to_u16(&rules.left)[..2], [connected, disconnected]);
+        let (connected, disconnected) = (0b0100_0111_1110, 0b1011_1000_0001);
+        assert_eq!(to_u16(&rules.right)[..2], [disconnected, connected]);
+        let (connected, disconnected) = (0b0011_1101_0110, 0b1100_0010_1001);
+        assert_eq!(to_u16(&rules.top)[..2], [disconnected, connected]);
+        let (connected, disconnected) = (0b0101_1101_1001, 0b1010_0010_0110);
+        assert_eq!(to_u16(&rules.bottom)[..2], [connected, disconnected]);
+    }
+
+    crate::tile_rules! {
+        const PIPES: u16 = [
+            CORNER,
+            Sides::TOP.union(Sides::RIGHT),
+            TEE,
+            Sides::LEFT.union(Sides::RIGHT).union(Sides::BOTTOM),
+            Sides::TOP.union(Sides::BOTTOM).union(Sides::RIGHT),
+            LINE,
+            Sides::ALL,
+            Sides::TOP.union(Sides::BOTTOM),
+            Sides::TOP.union(Sides::BOTTOM).union(Sides::LEFT),
+            Sides::TOP.union(Sides::LEFT),
+            Sides::BOTTOM.union(Sides::RIGHT),
+            Sides::NONE,
+        ];
+    }
+
+    #[test]
+    /// Masks built at compile time match the rules built at runtime
+    fn const_tile_rules() {
+        assert_eq!(PIPES.left.len(), 12);
+        let rules = TileRules::<u16>::new(&[
+            CORNER,
+            Sides::TOP | Sides::RIGHT,
+            TEE,
+            Sides::LEFT | Sides::RIGHT | Sides::BOTTOM,
+            Sides::TOP | Sides::BOTTOM | Sides::RIGHT,
+            LINE,
+            Sides::ALL,
+            Sides::TOP | Sides::BOTTOM,
+            Sides::TOP | Sides::BOTTOM | Sides::LEFT,
+            Sides::TOP | Sides::LEFT,
+            Sides::BOTTOM | Sides::RIGHT,
+            Sides::NONE,
+        ]);
+        for direction in Direction::ALL {
+            assert_eq!(PIPES.masks(direction), rules.masks(direction));
+        }
+    }
+
+    #[test]
+    /// Each neighbor is matched against the mask for its direction
+    fn reducer_directions() {
+        let tiles = [LINE, Sides::TOP | Sides::BOTTOM, Sides::NONE];
+        let reducer = TileRules::<u8>::new(&tiles).into_reducer::<3>(3);
+        let line = Cell::<u8, 3>::collapsed(0);
+
+        // A line beside a cell must continue into it
+        assert_eq!(reducer(vec![(3, &line)], 4).into_deny().data, 0b110);
+        assert_eq!(reducer(vec![(5, &line)], 4).into_deny().data, 0b110);
+        // A line above or below a cell leaves nothing to connect to
+        assert_eq!(reducer(vec![(1, &line)], 4).into_deny().data, 0b010);
+        assert_eq!(reducer(vec![(7, &line)], 4).into_deny().data, 0b010);
+        // Cells on either side of a row break aren't adjacent
+        assert_eq!(reducer(vec![(2, &line)], 3).into_deny().data, 0b000);
+    }
+
+    /// Black and white squares which may only sit beside the other color
+    struct Checkers;
+
+    impl TileSet for Checkers {
+        const N: usize = 2;
+
+        fn glyph(tile: usize) -> char {
+            ['#', ' '][tile]
+        }
+
+        fn allowed(tile: usize, neighbor: usize, _: Direction) -> bool {
+            tile != neighbor
+        }
+    }
+
+    #[test]
+    fn tile_set_rules() {
+        let rules = Checkers::rules::<u8>();
+        for masks in [&rules.left, &rules.right, &rules.top, &rules.bottom] {
+            assert_eq!(
+                masks.iter().map(|m| m.data).collect::<Vec<_>>(),
+                [0b01, 0b10]
+            );
+        }
+    }
+
+    #[test]
+    fn tile_set_glyphs() {
+        assert_eq!(Checkers::tile(' '), Some(1));
+        assert_eq!(Checkers::tile('x'), None);
+
+        let cell = Checkers::parse_cell::<u8, 2>('#', '.').unwrap();
+        assert_eq!(cell, Cell::reduced(0));
+        assert_eq!(Checkers::render_cell(&cell, '.'), '#');
+
+        let cell = Checkers::parse_cell::<u8, 2>('.', '.').unwrap();
+        assert!(cell.is_unknown());
+        assert_eq!(Checkers::render_cell(&cell, '.'), '.');
+        assert_eq!(Checkers::parse_cell::<u8, 2>('x', '.'), None);
+    }
+
+    #[test]
+    fn directed_reducer_directions() {
+        let tiles = [LINE, Sides::TOP | Sides::BOTTOM, Sides::NONE];
+        let reducer = TileRules::<u8>::new(&tiles).into_directed_reducer::<3>();
+        let line = Cell::<u8, 3>::collapsed(0);
+
+        let actual = reducer(vec![(0, Direction::Left, &line)], 1)
+            .into_deny()
+            .data;
+        assert_eq!(actual, 0b110);
+        let actual = reducer(vec![(0, Direction::Up, &line)], 1).into_deny().data;
+        assert_eq!(actual, 0b010);
+    }
+}